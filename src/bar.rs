@@ -0,0 +1,54 @@
+use std::env;
+
+/// Partially filled cells, in eighths of a cell.
+const EIGHTHS: [char; 8] = [' ', '▏', '▎', '▍', '▌', '▋', '▊', '▉'];
+
+/// The narrowest bar worth drawing, including its brackets.
+const MIN_WIDTH: usize = 10;
+
+/// Returns whether the locale uses UTF-8, going by LC_ALL, LC_CTYPE and LANG in that order.
+pub fn is_unicode() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value = value.to_ascii_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        .unwrap_or(false)
+}
+
+/// Renders a progress bar `width` columns wide, brackets included, filled to `fraction`.
+/// Unicode bars fill in eighths of a cell, ASCII bars in whole cells.
+fn render(fraction: f64, width: usize, unicode: bool) -> String {
+    let cells = width.saturating_sub(2);
+    let fraction = fraction.clamp(0.0, 1.0);
+
+    let mut bar = String::from("[");
+    if unicode {
+        let eighths = (fraction * (cells * 8) as f64) as usize;
+        bar.push_str(&"█".repeat(eighths / 8));
+        if eighths / 8 < cells {
+            bar.push(EIGHTHS[eighths % 8]);
+            bar.push_str(&" ".repeat(cells - eighths / 8 - 1));
+        }
+    } else {
+        let filled = (fraction * cells as f64) as usize;
+        bar.push_str(&"#".repeat(filled));
+        bar.push_str(&"-".repeat(cells - filled));
+    }
+    bar.push(']');
+
+    bar
+}
+
+/// Renders a progress bar filling what is left of a terminal line after `used` columns of
+/// text, or `None` if too little is left.
+pub fn fit(fraction: f64, used: usize, columns: usize, unicode: bool) -> Option<String> {
+    // Leave a space after the text, and the last column free so the line does not wrap.
+    let gap = if used > 0 { 1 } else { 0 };
+    let width = columns.saturating_sub(used + gap + 1);
+
+    (width >= MIN_WIDTH).then(|| render(fraction, width, unicode))
+}
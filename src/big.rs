@@ -0,0 +1,157 @@
+use crate::terminal;
+
+/// Height of a glyph in rows.
+const HEIGHT: usize = 5;
+
+/// Returns the seven-segment-style glyph for a character, drawn with `#` for filled pixels.
+fn glyph(c: char) -> Option<[&'static str; HEIGHT]> {
+    let glyph = match c {
+        '0' => ["###", "#.#", "#.#", "#.#", "###"],
+        '1' => ["..#", "..#", "..#", "..#", "..#"],
+        '2' => ["###", "..#", "###", "#..", "###"],
+        '3' => ["###", "..#", "###", "..#", "###"],
+        '4' => ["#.#", "#.#", "###", "..#", "..#"],
+        '5' => ["###", "#..", "###", "..#", "###"],
+        '6' => ["###", "#..", "###", "#.#", "###"],
+        '7' => ["###", "..#", "..#", "..#", "..#"],
+        '8' => ["###", "#.#", "###", "#.#", "###"],
+        '9' => ["###", "#.#", "###", "..#", "###"],
+        ':' => [".", "#", ".", "#", "."],
+        '.' => [".", ".", ".", ".", "#"],
+        '-' => ["...", "...", "###", "...", "..."],
+        '|' => ["#", "#", "#", "#", "#"],
+        '%' => ["#.#", "..#", ".#.", "#..", "#.#"],
+        ' ' => [".", ".", ".", ".", "."],
+        'd' => ["..#", "..#", "###", "#.#", "###"],
+        'h' => ["#..", "#..", "###", "#.#", "#.#"],
+        'm' => [".....", ".....", "####.", "#.#.#", "#.#.#"],
+        's' => ["...", ".##", "#..", "..#", "##."],
+        'c' => ["...", "...", "###", "#..", "###"],
+        'i' => ["#", ".", "#", "#", "#"],
+        'j' => ["..#", "...", "..#", "..#", "##."],
+        'n' => ["...", "...", "###", "#.#", "#.#"],
+        'D' => ["##.", "#.#", "#.#", "#.#", "##."],
+        'H' => ["#.#", "#.#", "###", "#.#", "#.#"],
+        'M' => ["#...#", "##.##", "#.#.#", "#...#", "#...#"],
+        'P' => ["###", "#.#", "###", "#..", "#.."],
+        'S' => [".##", "#..", ".#.", "..#", "##."],
+        'T' => ["###", ".#.", ".#.", ".#.", ".#."],
+        _ => return None,
+    };
+
+    Some(glyph)
+}
+
+/// Renders text as rows of glyphs, each pixel `scale` columns wide and drawn with a full
+/// block, or with `#` if the terminal cannot show Unicode. Characters without a glyph, such as
+/// the Japanese unit suffixes, are kept as they are on the bottom row, like a subscript.
+fn render(text: &str, scale: usize, unicode: bool) -> [String; HEIGHT] {
+    let block = if unicode { "█" } else { "#" };
+
+    let mut rows: [String; HEIGHT] = Default::default();
+
+    for (index, c) in text.chars().enumerate() {
+        if index > 0 {
+            rows.iter_mut()
+                .for_each(|row| row.push_str(&" ".repeat(scale)));
+        }
+
+        match glyph(c) {
+            Some(glyph) => {
+                for (row, pixels) in rows.iter_mut().zip(glyph) {
+                    for pixel in pixels.chars() {
+                        let fill = if pixel == '#' { block } else { " " };
+                        row.push_str(&fill.repeat(scale));
+                    }
+                }
+            }
+            None => {
+                let width = terminal::width(&c.to_string());
+                for (number, row) in rows.iter_mut().enumerate() {
+                    if number == HEIGHT - 1 {
+                        row.push(c);
+                    } else {
+                        row.push_str(&" ".repeat(width));
+                    }
+                }
+            }
+        }
+    }
+
+    rows
+}
+
+/// Renders each text in large glyphs, one below the other, as wide as fits in `columns`.
+/// Texts are returned as they are if even the narrowest glyphs do not fit.
+pub fn lines(texts: &[String], columns: usize, unicode: bool) -> Vec<String> {
+    let widest = |scale| {
+        texts
+            .iter()
+            .map(|text| terminal::width(&render(text, scale, unicode)[0]))
+            .max()
+            .unwrap_or(0)
+    };
+
+    match [2, 1].into_iter().find(|&scale| widest(scale) <= columns) {
+        Some(scale) => texts
+            .iter()
+            .enumerate()
+            .flat_map(|(index, text)| {
+                let gap = (index > 0).then(String::new);
+                gap.into_iter().chain(render(text, scale, unicode))
+            })
+            .collect(),
+        None => texts.to_vec(),
+    }
+}
+
+/// Returns the escape sequences that draw each text in large glyphs, with the SGR escape
+/// sequence `color` if any, centred in a terminal of the given size, followed by an optional
+/// footer in plain text. Texts that do not fit are drawn as they are.
+///
+/// Every row is redrawn in place, erasing only around the text, so that repainting does not
+/// flicker the way clearing the whole screen first does.
+pub fn frame(
+    texts: &[String],
+    footer: Option<&str>,
+    color: Option<&str>,
+    (columns, rows): (usize, usize),
+    unicode: bool,
+) -> String {
+    let mut lines = lines(texts, columns, unicode);
+    let clock_lines = lines.len();
+
+    if let Some(footer) = footer {
+        lines.push(String::new());
+        lines.push(footer.to_string());
+    }
+
+    let (color, reset) = match color {
+        Some(color) => (color, "\x1b[0m"),
+        None => ("", ""),
+    };
+
+    let top = rows.saturating_sub(lines.len()) / 2;
+    let mut frame = String::new();
+
+    for row in 0..rows {
+        let Some(line) = row.checked_sub(top).and_then(|index| lines.get(index)) else {
+            frame.push_str(&format!("\x1b[{};1H\x1b[2K", row + 1));
+            continue;
+        };
+
+        let left = columns.saturating_sub(terminal::width(line)) / 2;
+        let (color, reset) = if row - top < clock_lines {
+            (color, reset)
+        } else {
+            ("", "")
+        };
+        frame.push_str(&format!(
+            "\x1b[{};{}H\x1b[1K{color}{line}{reset}\x1b[K",
+            row + 1,
+            left + 1
+        ));
+    }
+
+    frame
+}
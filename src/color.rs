@@ -0,0 +1,347 @@
+use std::env;
+use std::time::Duration;
+
+use clap::{Args, ValueEnum};
+
+use crate::config::Config;
+use crate::parse::parse_duration;
+use crate::terminal;
+
+/// When to color the output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum When {
+    /// Color if stdout is a terminal that supports it and NO_COLOR is not set
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+/// The colors a terminal can show.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Palette {
+    /// The 16 standard ANSI colors
+    #[value(name = "16")]
+    Basic,
+    /// The xterm 256-color palette
+    #[value(name = "256")]
+    Indexed,
+    /// 24-bit RGB colors
+    #[value(name = "truecolor")]
+    TrueColor,
+}
+
+/// A foreground color: one of the 16 standard colors, a 256-color palette index or RGB.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Color {
+    Basic(u8),
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+/// Names of the 16 standard colors, in palette order.
+const NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright-black",
+    "bright-red",
+    "bright-green",
+    "bright-yellow",
+    "bright-blue",
+    "bright-magenta",
+    "bright-cyan",
+    "bright-white",
+];
+
+/// RGB values of the 16 standard colors, as xterm shows them.
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// Levels of the six steps of each channel in the 256-color cube.
+const CUBE_STEPS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+impl Color {
+    /// Parses a color name (e.g., `yellow`, `bright-red`), a 256-color palette index (e.g.,
+    /// `208`) or an RGB hex triplet (e.g., `#ff8700`).
+    pub fn parse(text: &str) -> Result<Color, String> {
+        let lowercase = text.to_ascii_lowercase();
+
+        if let Some(index) = NAMES.iter().position(|&name| name == lowercase) {
+            return Ok(Color::Basic(index as u8));
+        }
+
+        if let Ok(index) = text.parse::<u8>() {
+            return Ok(Color::Indexed(index));
+        }
+
+        let hex = text
+            .strip_prefix('#')
+            .filter(|hex| hex.len() == 6 && hex.is_ascii());
+        if let Some(hex) = hex {
+            let channel = |index: usize| u8::from_str_radix(&hex[index..index + 2], 16);
+            if let (Ok(red), Ok(green), Ok(blue)) = (channel(0), channel(2), channel(4)) {
+                return Ok(Color::Rgb(red, green, blue));
+            }
+        }
+
+        Err(format!(
+            "expected a color name such as `yellow`, a palette index from 0 to 255 or `#rrggbb`, found `{text}`"
+        ))
+    }
+
+    /// Returns the RGB value of the color.
+    fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Basic(index) => BASIC_RGB[index as usize],
+            Color::Indexed(index @ 0..=15) => BASIC_RGB[index as usize],
+            Color::Indexed(index @ 16..=231) => {
+                let index = index - 16;
+                (
+                    CUBE_STEPS[(index / 36) as usize],
+                    CUBE_STEPS[(index / 6 % 6) as usize],
+                    CUBE_STEPS[(index % 6) as usize],
+                )
+            }
+            Color::Indexed(index) => {
+                let level = 8 + (index - 232) * 10;
+                (level, level, level)
+            }
+            Color::Rgb(red, green, blue) => (red, green, blue),
+        }
+    }
+
+    /// Returns the SGR parameters that select the color as foreground, approximating it
+    /// with the nearest color if the palette cannot show it.
+    fn sgr(self, palette: Palette) -> String {
+        match (self, palette) {
+            (Color::Basic(index), _) => basic_sgr(index),
+            (Color::Indexed(index), Palette::Indexed | Palette::TrueColor) => {
+                format!("38;5;{index}")
+            }
+            (Color::Rgb(red, green, blue), Palette::TrueColor) => {
+                format!("38;2;{red};{green};{blue}")
+            }
+            (Color::Rgb(..), Palette::Indexed) => format!("38;5;{}", nearest_indexed(self.rgb())),
+            (_, Palette::Basic) => basic_sgr(nearest_basic(self.rgb())),
+        }
+    }
+}
+
+/// Returns the SGR parameter for one of the 16 standard colors.
+fn basic_sgr(index: u8) -> String {
+    if index < 8 {
+        format!("{}", 30 + index)
+    } else {
+        format!("{}", 90 + index - 8)
+    }
+}
+
+/// Returns the squared distance between two RGB colors.
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+/// Returns the standard color closest to an RGB color.
+fn nearest_basic(rgb: (u8, u8, u8)) -> u8 {
+    (0..16u8)
+        .min_by_key(|&index| distance(rgb, BASIC_RGB[index as usize]))
+        .unwrap_or(7)
+}
+
+/// Returns the index of the color in the 256-color cube or grayscale ramp closest to an RGB
+/// color.
+fn nearest_indexed(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255u8)
+        .min_by_key(|&index| distance(rgb, Color::Indexed(index).rgb()))
+        .unwrap_or(231)
+}
+
+/// Command-line flags for coloring the remaining time. Flags that are not given are read
+/// from the configuration file.
+#[derive(Debug, Clone, Args)]
+pub struct ColorOptions {
+    /// When to color the output by the time remaining. This and the other color options can
+    /// also be set in the configuration file described below.
+    #[arg(long = "color", value_name = "WHEN", value_enum)]
+    pub when: Option<When>,
+
+    /// The colors the terminal can show. Detected from COLORTERM and TERM by default.
+    #[arg(long = "palette", value_enum)]
+    pub palette: Option<Palette>,
+
+    /// Turn the output the warning color with this much time left [default: 1m].
+    #[arg(long = "warn-at", value_name = "DURATION", value_parser = parse_threshold)]
+    pub warn_at: Option<Duration>,
+
+    /// Turn the output the critical color with this much time left [default: 10s].
+    #[arg(long = "critical-at", value_name = "DURATION", value_parser = parse_threshold)]
+    pub critical_at: Option<Duration>,
+
+    /// Make the output blink in inverse video with this much time left [default: 3s].
+    #[arg(long = "blink-at", value_name = "DURATION", value_parser = parse_threshold)]
+    pub blink_at: Option<Duration>,
+
+    /// The warning color: a name, a palette index or #rrggbb [default: yellow].
+    #[arg(long = "warn-color", value_name = "COLOR", value_parser = Color::parse)]
+    pub warn_color: Option<Color>,
+
+    /// The critical color: a name, a palette index or #rrggbb [default: red].
+    #[arg(long = "critical-color", value_name = "COLOR", value_parser = Color::parse)]
+    pub critical_color: Option<Color>,
+}
+
+/// Parses a threshold given as a single duration argument.
+fn parse_threshold(text: &str) -> Result<Duration, String> {
+    parse_duration(vec![text.to_string()]).map_err(|error| error.kind.to_string())
+}
+
+/// Parses a value the way clap parses a `ValueEnum` flag.
+fn parse_value<T: ValueEnum>(text: &str) -> Result<T, String> {
+    T::from_str(text, true)
+}
+
+/// The colors of the remaining time, by how much of it is left.
+#[derive(Debug, Clone)]
+pub struct Colors {
+    palette: Palette,
+    warn_at: Duration,
+    critical_at: Duration,
+    blink_at: Duration,
+    warn_color: Color,
+    critical_color: Color,
+}
+
+impl Colors {
+    /// Settles the coloring from the flags, falling back to the configuration file and then to
+    /// the defaults. Returns `None` if the output should not be colored.
+    pub fn new(options: &ColorOptions, config: &Config) -> Result<Option<Colors>, String> {
+        config.check_keys(&[
+            "color",
+            "palette",
+            "warn-at",
+            "critical-at",
+            "blink-at",
+            "warn-color",
+            "critical-color",
+        ])?;
+
+        let when = match options.when {
+            Some(when) => when,
+            None => config.get("color", parse_value)?.unwrap_or(When::Auto),
+        };
+
+        let palette = match options.palette {
+            Some(palette) => palette,
+            None => config
+                .get("palette", parse_value)?
+                .unwrap_or_else(detect_palette),
+        };
+
+        let duration = |flag: Option<Duration>, key, default| -> Result<Duration, String> {
+            match flag {
+                Some(duration) => Ok(duration),
+                None => Ok(config.get(key, parse_threshold)?.unwrap_or(default)),
+            }
+        };
+        let color = |flag: Option<Color>, key, default| -> Result<Color, String> {
+            match flag {
+                Some(color) => Ok(color),
+                None => Ok(config.get(key, Color::parse)?.unwrap_or(default)),
+            }
+        };
+
+        // Settle every setting before deciding whether to color, so that mistakes in the
+        // configuration file are reported either way.
+        let colors = Colors {
+            palette,
+            warn_at: duration(options.warn_at, "warn-at", Duration::from_secs(60))?,
+            critical_at: duration(options.critical_at, "critical-at", Duration::from_secs(10))?,
+            blink_at: duration(options.blink_at, "blink-at", Duration::from_secs(3))?,
+            warn_color: color(options.warn_color, "warn-color", Color::Basic(3))?,
+            critical_color: color(options.critical_color, "critical-color", Color::Basic(1))?,
+        };
+
+        let enabled = match when {
+            When::Always => true,
+            When::Never => false,
+            When::Auto => is_color_terminal(),
+        };
+
+        Ok(enabled.then_some(colors))
+    }
+
+    /// Returns the SGR escape sequence for output with `remaining` time left, or `None` if it
+    /// is not colored yet.
+    pub fn escape(&self, remaining: Duration) -> Option<String> {
+        let mut parameters = Vec::new();
+
+        if remaining <= self.critical_at {
+            parameters.push(self.critical_color.sgr(self.palette));
+        } else if remaining <= self.warn_at {
+            parameters.push(self.warn_color.sgr(self.palette));
+        }
+
+        if remaining <= self.blink_at {
+            parameters.push("5;7".to_string());
+        }
+
+        (!parameters.is_empty()).then(|| format!("\x1b[{}m", parameters.join(";")))
+    }
+
+    /// Returns the SGR escape sequence for output counting past the end: the critical color,
+    /// without blinking.
+    pub fn overtime(&self) -> String {
+        format!("\x1b[{}m", self.critical_color.sgr(self.palette))
+    }
+}
+
+/// Returns whether stdout is a terminal that shows colors and NO_COLOR is not set.
+fn is_color_terminal() -> bool {
+    if env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty()) {
+        return false;
+    }
+
+    if env::var("TERM").map_or(true, |term| term == "dumb") {
+        return false;
+    }
+
+    terminal::is_terminal()
+}
+
+/// Guesses the palette of the terminal from COLORTERM and TERM.
+fn detect_palette() -> Palette {
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+    if colorterm == "truecolor" || colorterm == "24bit" {
+        return Palette::TrueColor;
+    }
+
+    if env::var("TERM").is_ok_and(|term| term.contains("256color")) {
+        return Palette::Indexed;
+    }
+
+    Palette::Basic
+}
@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+/// A `key = value` line of the configuration file.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub line: usize,
+    pub key: String,
+    pub value: String,
+}
+
+/// Settings read from `$XDG_CONFIG_HOME/snore/config`, or `~/.config/snore/config`.
+///
+/// Each line holds a `key = value` pair, named like the command-line flag that overrides it
+/// (e.g., `warn-at = 5m`). Blank lines and lines starting with `#` are ignored.
+#[derive(Debug, Default)]
+pub struct Config {
+    pub path: PathBuf,
+    pub entries: Vec<Entry>,
+}
+
+impl Config {
+    /// Reads the configuration file. A missing file yields an empty configuration.
+    pub fn load() -> Result<Config, String> {
+        let Some(path) = path() else {
+            return Ok(Config::default());
+        };
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Config::default()),
+            Err(error) => return Err(format!("{}: {error}", path.display())),
+        };
+
+        let mut entries = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(format!(
+                    "{}, line {}: expected `key = value`",
+                    path.display(),
+                    index + 1
+                ));
+            };
+
+            entries.push(Entry {
+                line: index + 1,
+                key: key.trim().to_string(),
+                value: value.trim().to_string(),
+            });
+        }
+
+        Ok(Config { path, entries })
+    }
+
+    /// Parses the value of a setting, if present. The last occurrence of a key wins.
+    pub fn get<T>(
+        &self,
+        key: &str,
+        parse: impl Fn(&str) -> Result<T, String>,
+    ) -> Result<Option<T>, String> {
+        let Some(entry) = self.entries.iter().rev().find(|entry| entry.key == key) else {
+            return Ok(None);
+        };
+
+        parse(&entry.value)
+            .map(Some)
+            .map_err(|error| format!("{}, line {}: {error}", self.path.display(), entry.line))
+    }
+
+    /// Rejects keys that are not one of `known`, which are most likely misspelled.
+    pub fn check_keys(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .entries
+            .iter()
+            .find(|entry| !known.contains(&entry.key.as_str()))
+        {
+            Some(entry) => Err(format!(
+                "{}, line {}: unknown setting `{}`",
+                self.path.display(),
+                entry.line,
+                entry.key
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Returns the path of the configuration file, if a home or config directory is known.
+fn path() -> Option<PathBuf> {
+    let directory = match env::var_os("XDG_CONFIG_HOME").filter(|value| !value.is_empty()) {
+        Some(directory) => PathBuf::from(directory),
+        None => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+
+    Some(directory.join("snore").join("config"))
+}
@@ -0,0 +1,517 @@
+use std::time::Duration;
+
+use clap::ValueEnum;
+
+use crate::locale::{self, Unit as Name};
+
+/// The smallest unit shown by the default style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Precision {
+    /// Milliseconds
+    Ms,
+    /// Centiseconds
+    Cs,
+    /// Seconds
+    S,
+    /// Minutes
+    M,
+    /// Drop leading zero units and show milliseconds only in the final minute
+    Adaptive,
+}
+
+/// Which way a duration is rounded to the precision.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    /// Towards zero, for time that has passed.
+    Down,
+    /// Away from zero, for time that is left, so a countdown only shows zero once it is over.
+    Up,
+}
+
+impl Precision {
+    /// Returns the smallest unit shown for a duration.
+    fn unit(self, duration: Duration) -> Duration {
+        match self {
+            Precision::Ms => Duration::from_millis(1),
+            Precision::Cs => Duration::from_millis(10),
+            Precision::S => Duration::from_secs(1),
+            Precision::M => Duration::from_secs(60),
+            Precision::Adaptive if duration < Duration::from_secs(60) => Duration::from_millis(1),
+            Precision::Adaptive => Duration::from_secs(1),
+        }
+    }
+
+    /// Rounds a duration to the smallest unit shown.
+    pub fn round(self, duration: Duration, rounding: Rounding) -> Duration {
+        let unit = self.unit(duration).as_nanos();
+        let nanos = duration.as_nanos();
+
+        let units = match rounding {
+            Rounding::Down => nanos / unit,
+            Rounding::Up => nanos.div_ceil(unit),
+        };
+
+        let nanos = units * unit;
+        Duration::new(
+            (nanos / 1_000_000_000) as u64,
+            (nanos % 1_000_000_000) as u32,
+        )
+    }
+}
+
+/// How a duration is displayed.
+#[derive(Debug, Clone)]
+pub enum Style {
+    /// `[Nd ]HHh MMm SSs mmmms`, down to the given precision.
+    Default(Precision),
+    /// An ISO 8601 duration such as `PT1H30M`.
+    Iso,
+    /// A user-supplied template such as `%H:%M:%S`.
+    Template(Template),
+    /// Prose such as `1 hour, 4 minutes`.
+    Human,
+}
+
+impl Style {
+    /// Formats a `Duration` in this style.
+    pub fn format(&self, duration: Duration) -> String {
+        match self {
+            Style::Default(Precision::Adaptive) => format_adaptive(duration),
+            Style::Default(precision) => format_duration(duration, *precision),
+            Style::Iso => format_iso8601(duration),
+            Style::Template(template) => template.format(duration),
+            Style::Human => format_human(duration),
+        }
+    }
+}
+
+/// Formats a `Duration` into a human-readable string, with the unit suffixes of the current
+/// locale.
+pub fn format_duration(seconds: Duration, precision: Precision) -> String {
+    let mut remaining_seconds = seconds.as_secs();
+    let mut remaining_milliseconds = seconds.as_millis();
+
+    let days = remaining_seconds / (60 * 60 * 24);
+    remaining_seconds %= 60 * 60 * 24;
+
+    let hours = remaining_seconds / (60 * 60);
+    remaining_seconds %= 60 * 60;
+
+    let minutes = remaining_seconds / 60;
+    remaining_seconds %= 60;
+
+    let seconds = remaining_seconds;
+
+    remaining_milliseconds %= 1000;
+    let milliseconds = remaining_milliseconds;
+
+    let locale = locale::current();
+    let [d, h, m, s, cs, ms] = [
+        Name::Day,
+        Name::Hour,
+        Name::Minute,
+        Name::Second,
+        Name::Centisecond,
+        Name::Millisecond,
+    ]
+    .map(|unit| locale.suffix(unit));
+
+    let mut parts = Vec::new();
+    if days > 0 {
+        parts.push(format!("{}{d}", days));
+    }
+
+    parts.push(match precision {
+        Precision::Ms | Precision::Adaptive => {
+            format!("{hours:02}{h} {minutes:02}{m} {seconds:02}{s} {milliseconds:03}{ms}")
+        }
+        Precision::Cs => {
+            let centiseconds = milliseconds / 10;
+            format!("{hours:02}{h} {minutes:02}{m} {seconds:02}{s} {centiseconds:02}{cs}")
+        }
+        Precision::S => format!("{hours:02}{h} {minutes:02}{m} {seconds:02}{s}"),
+        Precision::M => format!("{hours:02}{h} {minutes:02}{m}"),
+    });
+
+    parts.join(" ")
+}
+
+/// Formats a `Duration` like `format_duration`, but starting at the largest unit that is not
+/// zero and showing milliseconds only below one minute (e.g., `1h 05m 00s` or `42s 120ms`).
+pub fn format_adaptive(duration: Duration) -> String {
+    let locale = locale::current();
+    let total = duration.as_secs();
+    let units = [
+        (total / (60 * 60 * 24), Name::Day),
+        (total / (60 * 60) % 24, Name::Hour),
+        (total / 60 % 60, Name::Minute),
+        (total % 60, Name::Second),
+    ];
+
+    let mut parts = Vec::new();
+    for (value, unit) in units {
+        if parts.is_empty() && value == 0 && unit != Name::Second {
+            continue;
+        }
+
+        let suffix = locale.suffix(unit);
+        if parts.is_empty() {
+            parts.push(format!("{value}{suffix}"));
+        } else {
+            parts.push(format!("{value:02}{suffix}"));
+        }
+    }
+
+    if total < 60 {
+        let suffix = locale.suffix(Name::Millisecond);
+        parts.push(format!("{:03}{suffix}", duration.subsec_millis()));
+    }
+
+    parts.join(" ")
+}
+
+/// Formats a `Duration` as an ISO 8601 duration (e.g., `P1DT2H3M4.5S`).
+/// Only days and smaller designators are emitted, since those have a fixed length.
+pub fn format_iso8601(duration: Duration) -> String {
+    let mut remaining_seconds = duration.as_secs();
+    let milliseconds = duration.subsec_millis();
+
+    let days = remaining_seconds / (60 * 60 * 24);
+    remaining_seconds %= 60 * 60 * 24;
+
+    let hours = remaining_seconds / (60 * 60);
+    remaining_seconds %= 60 * 60;
+
+    let minutes = remaining_seconds / 60;
+    remaining_seconds %= 60;
+
+    let seconds = remaining_seconds;
+
+    let mut iso = String::from("P");
+    if days > 0 {
+        iso.push_str(&format!("{days}D"));
+    }
+
+    let mut time = String::new();
+    if hours > 0 {
+        time.push_str(&format!("{hours}H"));
+    }
+    if minutes > 0 {
+        time.push_str(&format!("{minutes}M"));
+    }
+    if milliseconds > 0 {
+        let fraction = format!("{milliseconds:03}");
+        time.push_str(&format!("{seconds}.{}S", fraction.trim_end_matches('0')));
+    } else if seconds > 0 || (days == 0 && time.is_empty()) {
+        time.push_str(&format!("{seconds}S"));
+    }
+
+    if !time.is_empty() {
+        iso.push('T');
+        iso.push_str(&time);
+    }
+
+    iso
+}
+
+/// Formats a `Duration` as prose with its two largest units, such as `1 hour, 4 minutes` or
+/// `1 second`, in the current locale. Smaller units are dropped.
+pub fn format_human(duration: Duration) -> String {
+    let locale = locale::current();
+    let total = duration.as_secs();
+    let units = [
+        (total / (60 * 60 * 24), Name::Day),
+        (total / (60 * 60) % 24, Name::Hour),
+        (total / 60 % 60, Name::Minute),
+        (total % 60, Name::Second),
+    ];
+
+    let largest = units
+        .iter()
+        .position(|&(value, _)| value > 0)
+        .unwrap_or(units.len() - 1);
+
+    units[largest..]
+        .iter()
+        .take(2)
+        .filter(|&&(value, _)| value > 0 || total == 0)
+        .map(|&(value, unit)| locale.quantity(value, unit))
+        .collect::<Vec<_>>()
+        .join(locale.list_separator())
+}
+
+/// A whole unit that a template can display, from largest to smallest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Unit {
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+}
+
+impl Unit {
+    fn seconds(self) -> u64 {
+        match self {
+            Unit::Days => 60 * 60 * 24,
+            Unit::Hours => 60 * 60,
+            Unit::Minutes => 60,
+            Unit::Seconds => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Piece {
+    Literal(String),
+    Field { unit: Unit, padded: bool },
+    Fraction { digits: u32 },
+}
+
+/// A parsed `--format` template.
+///
+/// `%D`, `%H`, `%M` and `%S` are days, hours, minutes and seconds, `%f` is milliseconds and
+/// `%Nf` the first N (1-9) fractional digits of the second, and `%%` is a literal `%`.
+/// Hours, minutes and seconds are zero-padded to two digits unless written as `%-H`, `%-M`
+/// or `%-S`. Each field holds what remains after the next larger unit in the template, so
+/// `%M:%S` shows 90 minutes as `90:00`.
+#[derive(Debug, Clone)]
+pub struct Template {
+    pieces: Vec<Piece>,
+    units: Vec<Unit>,
+}
+
+impl Template {
+    /// Parses a template, rejecting unknown or incomplete directives.
+    pub fn parse(text: &str) -> Result<Template, String> {
+        let mut pieces = Vec::new();
+        let mut literal = String::new();
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                literal.push(c);
+                continue;
+            }
+
+            if chars.peek() == Some(&'%') {
+                chars.next();
+                literal.push('%');
+                continue;
+            }
+
+            let padded = chars.next_if_eq(&'-').is_none();
+            let digits = chars
+                .next_if(|c| c.is_ascii_digit())
+                .map(|c| c as u32 - '0' as u32);
+
+            let piece = match (chars.next(), digits) {
+                (Some('D'), None) => Piece::Field {
+                    unit: Unit::Days,
+                    padded,
+                },
+                (Some('H'), None) => Piece::Field {
+                    unit: Unit::Hours,
+                    padded,
+                },
+                (Some('M'), None) => Piece::Field {
+                    unit: Unit::Minutes,
+                    padded,
+                },
+                (Some('S'), None) => Piece::Field {
+                    unit: Unit::Seconds,
+                    padded,
+                },
+                (Some('f'), Some(digits @ 1..=9)) => Piece::Fraction { digits },
+                (Some('f'), None) => Piece::Fraction { digits: 3 },
+                (Some(c), _) => {
+                    let flag = if padded { "" } else { "-" };
+                    let digits = digits.map(|digits| digits.to_string()).unwrap_or_default();
+                    return Err(format!("unknown directive '%{flag}{digits}{c}'"));
+                }
+                (None, _) => return Err("incomplete directive at the end".to_string()),
+            };
+
+            if !literal.is_empty() {
+                pieces.push(Piece::Literal(std::mem::take(&mut literal)));
+            }
+            pieces.push(piece);
+        }
+
+        if !literal.is_empty() {
+            pieces.push(Piece::Literal(literal));
+        }
+
+        let units = pieces
+            .iter()
+            .filter_map(|piece| match piece {
+                Piece::Field { unit, .. } => Some(*unit),
+                _ => None,
+            })
+            .collect();
+
+        Ok(Template { pieces, units })
+    }
+
+    /// Formats a `Duration` according to this template.
+    pub fn format(&self, duration: Duration) -> String {
+        let total = duration.as_secs();
+        let mut text = String::new();
+
+        for piece in &self.pieces {
+            match piece {
+                Piece::Literal(literal) => text.push_str(literal),
+                Piece::Field { unit, padded } => {
+                    // Only the next larger unit in the template is taken out of the total.
+                    let above = self.units.iter().filter(|&above| above < unit).max();
+                    let value = match above {
+                        Some(above) => total % above.seconds(),
+                        None => total,
+                    } / unit.seconds();
+
+                    if *padded && *unit != Unit::Days {
+                        text.push_str(&format!("{value:02}"));
+                    } else {
+                        text.push_str(&value.to_string());
+                    }
+                }
+                Piece::Fraction { digits } => {
+                    let fraction = duration.subsec_nanos() / 10u32.pow(9 - digits);
+                    text.push_str(&format!("{fraction:0width$}", width = *digits as usize));
+                }
+            }
+        }
+
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::parse::parse_duration;
+
+    fn format(template: &str, duration: Duration) -> String {
+        Template::parse(template).unwrap().format(duration)
+    }
+
+    fn secs(seconds: f64) -> Duration {
+        Duration::from_secs_f64(seconds)
+    }
+
+    #[test]
+    fn templates_fill_in_fields() {
+        assert_eq!(format("%H:%M:%S", secs(3_725.5)), "01:02:05");
+        assert_eq!(format("%-H:%-M:%-S", secs(3_725.5)), "1:2:5");
+        assert_eq!(format("%-M:%S", secs(65.0)), "1:05");
+        assert_eq!(format("%D days %H:%M", secs(90_000.0)), "1 days 01:00");
+        assert_eq!(format("100%% done", secs(0.0)), "100% done");
+    }
+
+    #[test]
+    fn templates_show_fractions_of_a_second() {
+        assert_eq!(format("%S.%f", secs(1.2345)), "01.234");
+        assert_eq!(format("%S.%3f", secs(1.2345)), "01.234");
+        assert_eq!(format("%S.%1f", secs(1.2345)), "01.2");
+        assert_eq!(format("%S.%6f", secs(1.05)), "01.050000");
+    }
+
+    #[test]
+    fn template_fields_carry_into_the_next_larger_unit_only() {
+        assert_eq!(format("%M:%S", secs(90.0 * 60.0)), "90:00");
+        assert_eq!(format("%H:%M", secs(26.0 * 60.0 * 60.0)), "26:00");
+        assert_eq!(format("%S", secs(3_725.0)), "3725");
+        assert_eq!(format("%D %S", secs(86_401.0)), "1 01");
+    }
+
+    #[test]
+    fn templates_reject_unknown_and_incomplete_directives() {
+        assert_eq!(Template::parse("%x").unwrap_err(), "unknown directive '%x'");
+        assert_eq!(
+            Template::parse("%-3H").unwrap_err(),
+            "unknown directive '%-3H'"
+        );
+        assert_eq!(
+            Template::parse("%0f").unwrap_err(),
+            "unknown directive '%0f'"
+        );
+        assert_eq!(
+            Template::parse("%H:%").unwrap_err(),
+            "incomplete directive at the end"
+        );
+        assert_eq!(
+            Template::parse("%-").unwrap_err(),
+            "incomplete directive at the end"
+        );
+    }
+
+    #[test]
+    fn rounds_elapsed_time_down_and_remaining_time_up() {
+        let duration = secs(61.2345);
+        assert_eq!(Precision::Ms.round(duration, Rounding::Down), secs(61.234));
+        assert_eq!(Precision::Ms.round(duration, Rounding::Up), secs(61.235));
+        assert_eq!(Precision::Cs.round(duration, Rounding::Up), secs(61.24));
+        assert_eq!(Precision::S.round(duration, Rounding::Down), secs(61.0));
+        assert_eq!(Precision::S.round(duration, Rounding::Up), secs(62.0));
+        assert_eq!(Precision::M.round(duration, Rounding::Down), secs(60.0));
+        assert_eq!(Precision::M.round(duration, Rounding::Up), secs(120.0));
+
+        // A whole unit stays as it is either way.
+        assert_eq!(Precision::S.round(secs(5.0), Rounding::Up), secs(5.0));
+
+        // Adaptive precision shows milliseconds only in the final minute.
+        assert_eq!(
+            Precision::Adaptive.round(duration, Rounding::Up),
+            secs(62.0)
+        );
+        assert_eq!(
+            Precision::Adaptive.round(secs(1.2345), Rounding::Up),
+            secs(1.235)
+        );
+    }
+
+    #[test]
+    fn formats_durations_in_the_default_styles() {
+        let duration = secs(3_725.042);
+        assert_eq!(
+            format_duration(duration, Precision::Ms),
+            "01h 02m 05s 042ms"
+        );
+        assert_eq!(format_duration(duration, Precision::Cs), "01h 02m 05s 04cs");
+        assert_eq!(format_duration(duration, Precision::S), "01h 02m 05s");
+        assert_eq!(format_duration(duration, Precision::M), "01h 02m");
+        assert_eq!(
+            format_duration(secs(90_061.0), Precision::S),
+            "1d 01h 01m 01s"
+        );
+
+        assert_eq!(format_adaptive(secs(3_900.0)), "1h 05m 00s");
+        assert_eq!(format_adaptive(secs(42.12)), "42s 120ms");
+
+        assert_eq!(format_human(secs(3_845.0)), "1 hour, 4 minutes");
+        assert_eq!(format_human(secs(1.0)), "1 second");
+        assert_eq!(format_human(secs(0.0)), "0 seconds");
+    }
+
+    #[test]
+    fn formats_iso_8601_durations() {
+        assert_eq!(format_iso8601(secs(0.0)), "PT0S");
+        assert_eq!(format_iso8601(secs(86_400.0)), "P1D");
+        assert_eq!(format_iso8601(secs(5_400.0)), "PT1H30M");
+        assert_eq!(format_iso8601(secs(0.25)), "PT0.25S");
+        assert_eq!(format_iso8601(secs(93_784.5)), "P1DT2H3M4.5S");
+    }
+
+    #[test]
+    fn iso_8601_durations_parse_back_to_the_same_time() {
+        for seconds in [0.0, 0.5, 59.0, 5_400.0, 86_400.0, 93_784.5, 1_209_600.125] {
+            let duration = secs(seconds);
+            let iso = format_iso8601(duration);
+            assert_eq!(
+                parse_duration(vec![iso.clone()]).unwrap(),
+                duration,
+                "{iso}"
+            );
+        }
+    }
+}
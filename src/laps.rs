@@ -0,0 +1,274 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::format::{format_duration, Precision, Rounding};
+use crate::locale;
+
+/// One recorded lap.
+#[derive(Debug, Clone, Copy)]
+pub struct Lap {
+    pub number: usize,
+    /// How long the lap took.
+    pub time: Duration,
+    /// The elapsed time at the end of the lap.
+    pub total: Duration,
+    /// How much longer the lap took than the fastest one.
+    pub delta: Duration,
+}
+
+/// Turns the elapsed times at which laps were recorded into laps.
+pub fn laps(totals: &[Duration]) -> Vec<Lap> {
+    let times: Vec<Duration> = totals
+        .iter()
+        .scan(Duration::ZERO, |previous, &total| {
+            let time = total.saturating_sub(*previous);
+            *previous = total;
+            Some(time)
+        })
+        .collect();
+    let best = times.iter().copied().min().unwrap_or_default();
+
+    times
+        .iter()
+        .zip(totals)
+        .enumerate()
+        .map(|(index, (&time, &total))| Lap {
+            number: index + 1,
+            time,
+            total,
+            delta: time - best,
+        })
+        .collect()
+}
+
+/// Formats a lap time for people to read, rounded down to `precision`.
+fn human(duration: Duration, precision: Precision) -> String {
+    format_duration(precision.round(duration, Rounding::Down), precision)
+}
+
+/// Returns a table of the laps with their times, running totals and how much slower each
+/// was than the fastest, one line per lap below a header.
+pub fn table(laps: &[Lap], precision: Precision) -> String {
+    let header = locale::current().lap_headers().map(String::from);
+    let rows: Vec<[String; 4]> = laps
+        .iter()
+        .map(|lap| {
+            [
+                lap.number.to_string(),
+                human(lap.time, precision),
+                human(lap.total, precision),
+                format!("+{}", human(lap.delta, precision)),
+            ]
+        })
+        .collect();
+
+    let mut widths = [0; 4];
+    for row in std::iter::once(&header).chain(&rows) {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    std::iter::once(&header)
+        .chain(&rows)
+        .map(|row| {
+            let [number, time, total, delta] = row;
+            let [number_width, time_width, total_width, _] = widths;
+            format!("{number:>number_width$}  {time:time_width$}  {total:total_width$}  {delta}")
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The file formats laps can be exported as.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Csv,
+    Json,
+}
+
+/// A file to write the laps to when the timer ends.
+#[derive(Debug, Clone)]
+pub struct Export {
+    pub path: PathBuf,
+    pub format: Format,
+}
+
+impl Export {
+    /// Parses the path to export to, picking the format by its extension.
+    pub fn parse(text: &str) -> Result<Export, String> {
+        let path = PathBuf::from(text);
+        let format = match path.extension().and_then(|extension| extension.to_str()) {
+            Some(extension) if extension.eq_ignore_ascii_case("csv") => Format::Csv,
+            Some(extension) if extension.eq_ignore_ascii_case("json") => Format::Json,
+            _ => {
+                return Err(format!(
+                    "expected a file ending in .csv or .json, found `{text}`"
+                ))
+            }
+        };
+
+        Ok(Export { path, format })
+    }
+
+    /// Writes the laps to the file, with times both in milliseconds and formatted like
+    /// `format_duration` at `precision`.
+    pub fn write(&self, laps: &[Lap], precision: Precision) -> Result<(), String> {
+        let text = match self.format {
+            Format::Csv => csv(laps, precision),
+            Format::Json => json(laps, precision),
+        };
+
+        fs::write(&self.path, text).map_err(|error| format!("{}: {error}", self.path.display()))
+    }
+}
+
+/// Returns the laps as CSV, one row per lap below a header.
+fn csv(laps: &[Lap], precision: Precision) -> String {
+    let mut text = String::from("lap,lap_ms,total_ms,delta_ms,lap_time,total_time,delta_time\n");
+
+    for lap in laps {
+        text.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            lap.number,
+            lap.time.as_millis(),
+            lap.total.as_millis(),
+            lap.delta.as_millis(),
+            csv_field(&human(lap.time, precision)),
+            csv_field(&human(lap.total, precision)),
+            csv_field(&human(lap.delta, precision)),
+        ));
+    }
+
+    text
+}
+
+/// Quotes a CSV field if it holds a separator, quote or line break.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// Returns the laps as a JSON array with one object per lap.
+fn json(laps: &[Lap], precision: Precision) -> String {
+    let objects: Vec<String> = laps
+        .iter()
+        .map(|lap| {
+            format!(
+                "  {{\"lap\": {}, \"lap_ms\": {}, \"total_ms\": {}, \"delta_ms\": {}, \"lap_time\": {}, \"total_time\": {}, \"delta_time\": {}}}",
+                lap.number,
+                lap.time.as_millis(),
+                lap.total.as_millis(),
+                lap.delta.as_millis(),
+                json_string(&human(lap.time, precision)),
+                json_string(&human(lap.total, precision)),
+                json_string(&human(lap.delta, precision)),
+            )
+        })
+        .collect();
+
+    if objects.is_empty() {
+        "[]\n".to_string()
+    } else {
+        format!("[\n{}\n]\n", objects.join(",\n"))
+    }
+}
+
+/// Quotes a string as a JSON string literal.
+fn json_string(text: &str) -> String {
+    let mut quoted = String::from("\"");
+
+    for character in text.chars() {
+        match character {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            character if character.is_control() => {
+                quoted.push_str(&format!("\\u{:04x}", character as u32))
+            }
+            character => quoted.push(character),
+        }
+    }
+
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(totals: &[u64]) -> Vec<Duration> {
+        totals.iter().copied().map(Duration::from_millis).collect()
+    }
+
+    #[test]
+    fn measures_laps_against_the_fastest() {
+        let laps = laps(&millis(&[1_500, 2_500, 4_000]));
+
+        let times: Vec<_> = laps.iter().map(|lap| lap.time.as_millis()).collect();
+        let deltas: Vec<_> = laps.iter().map(|lap| lap.delta.as_millis()).collect();
+        assert_eq!(times, [1_500, 1_000, 1_500]);
+        assert_eq!(deltas, [500, 0, 500]);
+        assert_eq!(laps[2].number, 3);
+        assert_eq!(laps[2].total, Duration::from_millis(4_000));
+    }
+
+    #[test]
+    fn quotes_csv_fields_with_separators_and_quotes() {
+        assert_eq!(csv_field("00h 01m 30s"), "00h 01m 30s");
+        assert_eq!(csv_field("1,5 s"), "\"1,5 s\"");
+        assert_eq!(csv_field("5\" over"), "\"5\"\" over\"");
+        assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    }
+
+    #[test]
+    fn escapes_json_strings() {
+        assert_eq!(json_string("1,5 s"), "\"1,5 s\"");
+        assert_eq!(json_string("5\" over"), "\"5\\\" over\"");
+        assert_eq!(json_string("a\\b\nc\u{1}"), "\"a\\\\b\\nc\\u0001\"");
+        assert_eq!(json_string("1分"), "\"1分\"");
+    }
+
+    #[test]
+    fn writes_one_row_or_object_per_lap() {
+        let laps = laps(&millis(&[1_500, 2_500]));
+
+        assert_eq!(
+            csv(&laps, Precision::S),
+            "lap,lap_ms,total_ms,delta_ms,lap_time,total_time,delta_time\n\
+             1,1500,1500,500,00h 00m 01s,00h 00m 01s,00h 00m 00s\n\
+             2,1000,2500,0,00h 00m 01s,00h 00m 02s,00h 00m 00s\n"
+        );
+        assert_eq!(
+            json(&laps[..1], Precision::S),
+            "[\n  {\"lap\": 1, \"lap_ms\": 1500, \"total_ms\": 1500, \"delta_ms\": 500, \
+             \"lap_time\": \"00h 00m 01s\", \"total_time\": \"00h 00m 01s\", \
+             \"delta_time\": \"00h 00m 00s\"}\n]\n"
+        );
+    }
+
+    #[test]
+    fn writes_a_header_or_an_empty_array_without_laps() {
+        assert_eq!(
+            csv(&[], Precision::Ms),
+            "lap,lap_ms,total_ms,delta_ms,lap_time,total_time,delta_time\n"
+        );
+        assert_eq!(json(&[], Precision::Ms), "[]\n");
+        assert!(laps(&[]).is_empty());
+    }
+
+    #[test]
+    fn picks_the_export_format_by_extension() {
+        assert_eq!(Export::parse("laps.CSV").unwrap().format, Format::Csv);
+        assert_eq!(Export::parse("out/laps.json").unwrap().format, Format::Json);
+        assert!(Export::parse("laps.txt").is_err());
+        assert!(Export::parse("laps").is_err());
+    }
+}
@@ -0,0 +1,354 @@
+use std::env;
+use std::sync::OnceLock;
+
+use clap::ValueEnum;
+
+use crate::parse::ErrorKind;
+
+/// A language that output and error messages can be shown in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Locale {
+    /// English
+    En,
+    /// German
+    De,
+    /// French
+    Fr,
+    /// Japanese
+    Ja,
+}
+
+/// A unit of time as it is named in output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Day,
+    Hour,
+    Minute,
+    Second,
+    Centisecond,
+    Millisecond,
+}
+
+/// The locale chosen at startup.
+static CURRENT: OnceLock<Locale> = OnceLock::new();
+
+/// Sets the locale for the rest of the program. Only the first call has an effect.
+pub fn set(locale: Locale) {
+    let _ = CURRENT.set(locale);
+}
+
+/// Returns the locale set at startup, or English if none was.
+pub fn current() -> Locale {
+    CURRENT.get().copied().unwrap_or(Locale::En)
+}
+
+impl Locale {
+    /// Picks the locale from LC_ALL, LC_TIME or LANG, in that order, falling back to English
+    /// for unset variables and unsupported languages.
+    pub fn from_env() -> Locale {
+        ["LC_ALL", "LC_TIME", "LANG"]
+            .iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Locale::from_name(&value))
+            .unwrap_or(Locale::En)
+    }
+
+    /// Parses a POSIX locale name such as `de_DE.UTF-8` by its language code.
+    fn from_name(name: &str) -> Option<Locale> {
+        let language = name.split(['_', '.', '@', '-']).next()?;
+
+        match language.to_ascii_lowercase().as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            "ja" => Some(Locale::Ja),
+            _ => None,
+        }
+    }
+
+    /// Returns the short suffix of a unit, as in `05m` or `05min`.
+    pub fn suffix(self, unit: Unit) -> &'static str {
+        match (self, unit) {
+            (Locale::En, Unit::Day) => "d",
+            (Locale::De, Unit::Day) => "T",
+            (Locale::Fr, Unit::Day) => "j",
+            (Locale::Ja, Unit::Day) => "日",
+            (Locale::Ja, Unit::Hour) => "時間",
+            (_, Unit::Hour) => "h",
+            (Locale::En, Unit::Minute) => "m",
+            (Locale::Ja, Unit::Minute) => "分",
+            (_, Unit::Minute) => "min",
+            (Locale::Ja, Unit::Second) => "秒",
+            (_, Unit::Second) => "s",
+            (Locale::Ja, Unit::Centisecond) => "センチ秒",
+            (_, Unit::Centisecond) => "cs",
+            (Locale::Ja, Unit::Millisecond) => "ミリ秒",
+            (_, Unit::Millisecond) => "ms",
+        }
+    }
+
+    /// Writes out a count of a unit in words, as in `1 hour` or `4 Minuten`.
+    pub fn quantity(self, count: u64, unit: Unit) -> String {
+        let (singular, plural) = match (self, unit) {
+            (Locale::En, Unit::Day) => ("day", "days"),
+            (Locale::En, Unit::Hour) => ("hour", "hours"),
+            (Locale::En, Unit::Minute) => ("minute", "minutes"),
+            (Locale::En, Unit::Second) => ("second", "seconds"),
+            (Locale::En, Unit::Centisecond) => ("centisecond", "centiseconds"),
+            (Locale::En, Unit::Millisecond) => ("millisecond", "milliseconds"),
+            (Locale::De, Unit::Day) => ("Tag", "Tage"),
+            (Locale::De, Unit::Hour) => ("Stunde", "Stunden"),
+            (Locale::De, Unit::Minute) => ("Minute", "Minuten"),
+            (Locale::De, Unit::Second) => ("Sekunde", "Sekunden"),
+            (Locale::De, Unit::Centisecond) => ("Hundertstelsekunde", "Hundertstelsekunden"),
+            (Locale::De, Unit::Millisecond) => ("Millisekunde", "Millisekunden"),
+            (Locale::Fr, Unit::Day) => ("jour", "jours"),
+            (Locale::Fr, Unit::Hour) => ("heure", "heures"),
+            (Locale::Fr, Unit::Minute) => ("minute", "minutes"),
+            (Locale::Fr, Unit::Second) => ("seconde", "secondes"),
+            (Locale::Fr, Unit::Centisecond) => ("centiseconde", "centisecondes"),
+            (Locale::Fr, Unit::Millisecond) => ("milliseconde", "millisecondes"),
+            (Locale::Ja, unit) => return format!("{count}{}", self.suffix(unit)),
+        };
+
+        // French treats zero as singular, English and German do not.
+        let is_singular = count == 1 || (self == Locale::Fr && count == 0);
+        let name = if is_singular { singular } else { plural };
+
+        format!("{count} {name}")
+    }
+
+    /// Returns the separator between the quantities of a duration written out in words.
+    pub fn list_separator(self) -> &'static str {
+        match self {
+            Locale::Ja => "",
+            _ => ", ",
+        }
+    }
+
+    /// Describes how much time is left, as in `4 minutes remaining`.
+    pub fn remaining(self, time: &str) -> String {
+        match self {
+            Locale::En => format!("{time} remaining"),
+            Locale::De => format!("noch {time}"),
+            Locale::Fr => format!("encore {time}"),
+            Locale::Ja => format!("残り{time}"),
+        }
+    }
+
+    /// Describes how much time has passed, as in `4 minutes elapsed`.
+    pub fn elapsed(self, time: &str) -> String {
+        match self {
+            Locale::En => format!("{time} elapsed"),
+            Locale::De => format!("{time} vergangen"),
+            Locale::Fr => format!("écoulé : {time}"),
+            Locale::Ja => format!("経過{time}"),
+        }
+    }
+
+    /// Describes the wall-clock time at which the timer ends.
+    pub fn ends_at(self, time: &str) -> String {
+        match self {
+            Locale::En => format!("ends at {time}"),
+            Locale::De => format!("endet um {time}"),
+            Locale::Fr => format!("se termine à {time}"),
+            Locale::Ja => format!("{time}に終了"),
+        }
+    }
+
+    /// Announces the target of `--until`.
+    pub fn waiting_until(self, target: &str) -> String {
+        match self {
+            Locale::En => format!("Waiting until {target}"),
+            Locale::De => format!("Warten bis {target}"),
+            Locale::Fr => format!("Attente jusqu'à {target}"),
+            Locale::Ja => format!("{target}まで待機します"),
+        }
+    }
+
+    /// Describes how long the timer has been paused, as in `paused 00h 00m 05s`.
+    pub fn paused(self, time: &str) -> String {
+        match self {
+            Locale::En => format!("paused {time}"),
+            Locale::De => format!("pausiert {time}"),
+            Locale::Fr => format!("en pause {time}"),
+            Locale::Ja => format!("一時停止{time}"),
+        }
+    }
+
+    /// Describes how far a timer ran past its end, as in `2 minutes over`.
+    pub fn overtime(self, time: &str) -> String {
+        match self {
+            Locale::En => format!("{time} over"),
+            Locale::De => format!("{time} überzogen"),
+            Locale::Fr => format!("{time} de dépassement"),
+            Locale::Ja => format!("{time}超過"),
+        }
+    }
+
+    /// Describes a lap, as in `lap 2: 00h 01m 05s`.
+    pub fn lap(self, number: usize, time: &str) -> String {
+        match self {
+            Locale::En => format!("lap {number}: {time}"),
+            Locale::De => format!("Runde {number}: {time}"),
+            Locale::Fr => format!("tour {number} : {time}"),
+            Locale::Ja => format!("ラップ{number}: {time}"),
+        }
+    }
+
+    /// Returns the column headers of the lap table: number, lap time, total and delta from
+    /// the fastest lap.
+    pub fn lap_headers(self) -> [&'static str; 4] {
+        match self {
+            Locale::En => ["lap", "time", "total", "behind best"],
+            Locale::De => ["Runde", "Zeit", "gesamt", "hinter Bestzeit"],
+            Locale::Fr => ["tour", "temps", "total", "écart au meilleur"],
+            Locale::Ja => ["ラップ", "タイム", "合計", "ベストとの差"],
+        }
+    }
+
+    /// Describes the keys that control the timer.
+    pub fn key_hints(self) -> &'static str {
+        match self {
+            Locale::En => "space pause · +/- minute · r restart · l lap · q quit",
+            Locale::De => "Leertaste Pause · +/- Minute · r neu starten · l Runde · q beenden",
+            Locale::Fr => "espace pause · +/- minute · r recommencer · l tour · q quitter",
+            Locale::Ja => "スペース 一時停止 · +/- 1分 · r 再開始 · l ラップ · q 終了",
+        }
+    }
+
+    /// Describes the keys that control the stopwatch.
+    pub fn stopwatch_hints(self) -> &'static str {
+        match self {
+            Locale::En => "space pause · r restart · l lap · q stop",
+            Locale::De => "Leertaste Pause · r neu starten · l Runde · q anhalten",
+            Locale::Fr => "espace pause · r recommencer · l tour · q arrêter",
+            Locale::Ja => "スペース 一時停止 · r 再開始 · l ラップ · q 停止",
+        }
+    }
+
+    /// Describes how to quit when keys cannot be read.
+    pub fn quit_hint(self) -> &'static str {
+        match self {
+            Locale::En => "Ctrl-C quit",
+            Locale::De => "Strg-C beenden",
+            Locale::Fr => "Ctrl-C quitter",
+            Locale::Ja => "Ctrl-C 終了",
+        }
+    }
+
+    /// Returns the word that introduces an error message.
+    pub fn error(self) -> &'static str {
+        match self {
+            Locale::En => "Error",
+            Locale::De => "Fehler",
+            Locale::Fr => "Erreur",
+            Locale::Ja => "エラー",
+        }
+    }
+
+    /// Points at the argument and column an error occurred in.
+    pub fn position(self, argument: usize, column: usize) -> String {
+        match self {
+            Locale::En => format!("argument {argument}, column {column}"),
+            Locale::De => format!("Argument {argument}, Spalte {column}"),
+            Locale::Fr => format!("argument {argument}, colonne {column}"),
+            Locale::Ja => format!("引数{argument}、{column}列目"),
+        }
+    }
+
+    /// Suggests a correction for the offending text of an error.
+    pub fn did_you_mean(self, suggestion: &str) -> String {
+        match self {
+            Locale::En => format!("help: did you mean `{suggestion}`?"),
+            Locale::De => format!("Hilfe: meinten Sie `{suggestion}`?"),
+            Locale::Fr => format!("aide : vouliez-vous dire `{suggestion}` ?"),
+            Locale::Ja => format!("ヒント: `{suggestion}` のことですか?"),
+        }
+    }
+
+    /// Reports a word the natural-language parser did not understand.
+    pub fn could_not_understand(self, word: &str) -> String {
+        match self {
+            Locale::En => format!("Could not understand \"{word}\""),
+            Locale::De => format!("„{word}“ wurde nicht verstanden"),
+            Locale::Fr => format!("Impossible de comprendre « {word} »"),
+            Locale::Ja => format!("「{word}」を理解できません"),
+        }
+    }
+
+    /// Returns the message for a kind of parsing error.
+    pub fn error_message(self, kind: ErrorKind) -> &'static str {
+        match self {
+            Locale::En => match kind {
+                ErrorKind::InvalidNumber => "Invalid number format",
+                ErrorKind::InvalidUnit => "Invalid unit format",
+                ErrorKind::MalformedClock => "Invalid clock format",
+                ErrorKind::MalformedIso8601 => "Invalid ISO 8601 duration",
+                ErrorKind::MalformedTime => "Invalid time format",
+                ErrorKind::MalformedDate => "Invalid date format",
+                ErrorKind::PastTarget => "Target time has already passed",
+                ErrorKind::UnknownTimeZone => "Unknown time zone",
+                ErrorKind::MalformedExpression => "Invalid expression",
+                ErrorKind::DivisionByZero => "Division by zero",
+                ErrorKind::Negative => "Duration is negative",
+                ErrorKind::NotFinite => "Number is not finite",
+                ErrorKind::Overflow => "Duration exceeds the maximum of 100 years",
+                ErrorKind::UnknownWord => "Could not understand word",
+                ErrorKind::UnexpectedEnd => "Unexpected end of input",
+            },
+            Locale::De => match kind {
+                ErrorKind::InvalidNumber => "Ungültiges Zahlenformat",
+                ErrorKind::InvalidUnit => "Ungültiges Einheitenformat",
+                ErrorKind::MalformedClock => "Ungültiges Uhrformat",
+                ErrorKind::MalformedIso8601 => "Ungültige ISO-8601-Dauer",
+                ErrorKind::MalformedTime => "Ungültiges Uhrzeitformat",
+                ErrorKind::MalformedDate => "Ungültiges Datumsformat",
+                ErrorKind::PastTarget => "Die Zielzeit ist bereits vergangen",
+                ErrorKind::UnknownTimeZone => "Unbekannte Zeitzone",
+                ErrorKind::MalformedExpression => "Ungültiger Ausdruck",
+                ErrorKind::DivisionByZero => "Division durch null",
+                ErrorKind::Negative => "Die Dauer ist negativ",
+                ErrorKind::NotFinite => "Die Zahl ist nicht endlich",
+                ErrorKind::Overflow => "Die Dauer überschreitet das Maximum von 100 Jahren",
+                ErrorKind::UnknownWord => "Unbekanntes Wort",
+                ErrorKind::UnexpectedEnd => "Unerwartetes Ende der Eingabe",
+            },
+            Locale::Fr => match kind {
+                ErrorKind::InvalidNumber => "Format de nombre invalide",
+                ErrorKind::InvalidUnit => "Format d'unité invalide",
+                ErrorKind::MalformedClock => "Format d'horloge invalide",
+                ErrorKind::MalformedIso8601 => "Durée ISO 8601 invalide",
+                ErrorKind::MalformedTime => "Format d'heure invalide",
+                ErrorKind::MalformedDate => "Format de date invalide",
+                ErrorKind::PastTarget => "L'heure cible est déjà passée",
+                ErrorKind::UnknownTimeZone => "Fuseau horaire inconnu",
+                ErrorKind::MalformedExpression => "Expression invalide",
+                ErrorKind::DivisionByZero => "Division par zéro",
+                ErrorKind::Negative => "La durée est négative",
+                ErrorKind::NotFinite => "Le nombre n'est pas fini",
+                ErrorKind::Overflow => "La durée dépasse le maximum de 100 ans",
+                ErrorKind::UnknownWord => "Mot incompris",
+                ErrorKind::UnexpectedEnd => "Fin de saisie inattendue",
+            },
+            Locale::Ja => match kind {
+                ErrorKind::InvalidNumber => "数値の形式が無効です",
+                ErrorKind::InvalidUnit => "単位の形式が無効です",
+                ErrorKind::MalformedClock => "時計形式が無効です",
+                ErrorKind::MalformedIso8601 => "ISO 8601 の期間が無効です",
+                ErrorKind::MalformedTime => "時刻の形式が無効です",
+                ErrorKind::MalformedDate => "日付の形式が無効です",
+                ErrorKind::PastTarget => "指定時刻はすでに過ぎています",
+                ErrorKind::UnknownTimeZone => "不明なタイムゾーンです",
+                ErrorKind::MalformedExpression => "式が無効です",
+                ErrorKind::DivisionByZero => "ゼロで除算しています",
+                ErrorKind::Negative => "期間が負の値です",
+                ErrorKind::NotFinite => "数値が有限ではありません",
+                ErrorKind::Overflow => "期間が上限の100年を超えています",
+                ErrorKind::UnknownWord => "理解できない単語です",
+                ErrorKind::UnexpectedEnd => "入力が途中で終わっています",
+            },
+        }
+    }
+}
@@ -1,13 +1,41 @@
+use std::ffi::{OsStr, OsString};
 use std::io::{stdout, Write};
 use std::process;
 use std::thread::sleep;
 use std::time::{Duration, Instant};
 
-use clap::Parser;
+use clap::{CommandFactory, FromArgMatches, Parser};
+
+use color::{ColorOptions, Colors};
+use config::Config;
+use format::{format_duration, Precision, Rounding, Style, Template};
+use laps::Export;
+use locale::Locale;
+use parse::parse_duration;
+use terminal::RawMode;
+use timer::Timer;
+use title::Title;
+
+mod bar;
+mod big;
+mod color;
+mod config;
+mod format;
+mod laps;
+mod locale;
+mod natural;
+mod parse;
+mod terminal;
+mod timer;
+mod title;
+mod tui;
+mod tz;
+mod until;
 
 /// A timer program that supports both ascending and descending formats.
 #[derive(Parser, Debug)]
 #[command(author="Lukas Karafiat")]
+#[command(after_help = AFTER_HELP)]
 struct Options {
     /// Print the time in ascending format.
     #[arg(short = 'a', long = "ascending")]
@@ -17,93 +45,280 @@ struct Options {
     #[arg(short = 'd', long = "descending")]
     print_descending_time: bool,
 
-    /// Timer durations in the format NUMBER[UNIT] (e.g., 10s, 5m).
-    #[arg(value_name = "NUMBER[UNIT]", required = true)]
+    /// Print how much of the time has passed, in percent.
+    #[arg(short = 'p', long = "percent")]
+    print_percentage: bool,
+
+    /// Print the wall-clock time at which the timer ends.
+    #[arg(short = 'e', long = "eta")]
+    print_end_time: bool,
+
+    /// Print the time as an ISO 8601 duration (e.g., PT1H30M).
+    #[arg(long = "iso", conflicts_with = "format")]
+    print_iso_time: bool,
+
+    /// Print the time using a template: %D days, %H hours, %M minutes, %S seconds,
+    /// %f milliseconds or %Nf for N fractional digits, and %% for a literal % (e.g., "%H:%M:%S").
+    /// Write %-H, %-M or %-S to drop the zero padding.
+    #[arg(long = "format", value_name = "TEMPLATE", value_parser = Template::parse)]
+    format: Option<Template>,
+
+    /// The smallest unit to show. Elapsed time is rounded down and remaining time up.
+    #[arg(long = "precision", value_name = "UNIT", value_enum, default_value_t = Precision::Ms)]
+    precision: Precision,
+
+    /// Print the time as prose (e.g., "1 hour, 4 minutes remaining"), one line every few seconds
+    /// for screen readers and logs.
+    #[arg(
+        long = "human",
+        conflicts_with_all = ["print_iso_time", "format", "precision", "big"]
+    )]
+    human: bool,
+
+    /// The language of units, prose and error messages. Defaults to the language of
+    /// LC_ALL, LC_TIME or LANG.
+    #[arg(long = "locale", value_enum)]
+    locale: Option<Locale>,
+
+    /// Draw the time in large digits centred in the terminal.
+    #[arg(long = "big")]
+    big: bool,
+
+    /// Take over the terminal with a full-screen display of the time, a progress bar, the
+    /// queued segments (one per duration argument) and a status line.
+    #[arg(long = "tui", conflicts_with_all = ["big", "human"])]
+    tui: bool,
+
+    /// Draw a progress bar of the elapsed time across the rest of the line.
+    #[arg(long = "bar")]
+    bar: bool,
+
+    /// Show the remaining time in the terminal window title, restoring the title on exit.
+    #[arg(long = "title")]
+    title: bool,
+
+    #[command(flatten)]
+    colors: ColorOptions,
+
+    /// Count up with no end, like a stopwatch, until stopped with Ctrl-C or q. The elapsed
+    /// time and laps are printed when it stops.
+    #[arg(
+        long = "stopwatch",
+        conflicts_with_all = ["until", "times", "print_descending_time", "print_percentage", "print_end_time", "bar"]
+    )]
+    stopwatch: bool,
+
+    /// Keep counting past zero, showing how far over the time is (e.g., +00h 00m 42s), until
+    /// stopped with Ctrl-C or q.
+    #[arg(long = "overtime", conflicts_with = "stopwatch")]
+    overtime: bool,
+
+    /// Write the laps to a file when the timer ends, as CSV or JSON by the file extension
+    /// (e.g., laps.csv).
+    #[arg(long = "export-laps", value_name = "FILE", value_parser = Export::parse)]
+    export_laps: Option<Export>,
+
+    /// Wait until an absolute time: HH:MM[:SS], YYYY-MM-DD HH:MM[:SS] or RFC 3339,
+    /// optionally followed by an IANA time zone (e.g., "09:00 America/New_York").
+    #[arg(
+        short = 'u',
+        long = "until",
+        value_name = "TIME",
+        conflicts_with = "times"
+    )]
+    until: Option<String>,
+
+    /// Timer durations in the format NUMBER[UNIT] (e.g., 10s, 5m, 1h30m15s, "2 hours"), HH:MM:SS or ISO 8601 (e.g., PT1H30M).
+    /// Units are ns, us, ms, s, m, h, d and w, or aliases such as sec, mins, hrs or weeks, in any case.
+    /// Durations can be combined with +, -, * and / (e.g., 2h-15m, 2h -15m, "(25m+5m)*4"), and plain English
+    /// such as "in 2 hours and 15 minutes" or "tomorrow at noon" works as well.
+    #[arg(value_name = "NUMBER[UNIT]", required_unless_present_any = ["until", "stopwatch"])]
     times: Vec<String>,
 }
 
-#[derive(Debug)]
-enum ParsingError {
-    InvalidNumber,
-    InvalidUnit,
-}
-
-impl std::fmt::Display for ParsingError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ParsingError::InvalidNumber => write!(f, "Error: Invalid number format"),
-            ParsingError::InvalidUnit => write!(f, "Error: Invalid unit format"),
-        }
+impl Options {
+    /// Parses the command line. Durations may start with a minus sign, as in `2h -15m`, but
+    /// are only read that way when the arguments do not parse otherwise, so that options can
+    /// still follow the durations.
+    fn try_parse_args<I, T>(args: I) -> Result<Options, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+
+        Options::try_parse_from(&args).or_else(|error| {
+            let negative = args.iter().skip(1).any(|arg| is_negative_duration(arg));
+            if error.kind() != clap::error::ErrorKind::UnknownArgument || !negative {
+                return Err(error);
+            }
+
+            Options::command()
+                .mut_arg("times", |arg| arg.allow_hyphen_values(true))
+                .try_get_matches_from(&args)
+                .and_then(|mut matches| Options::from_arg_matches_mut(&mut matches))
+                .map_err(|_| error)
+        })
     }
 }
 
-impl std::error::Error for ParsingError {}
+/// Returns whether a command-line argument is a duration that starts with a minus sign, such as
+/// `-15m` or `-(5m+1h)`, rather than an option.
+fn is_negative_duration(arg: &OsStr) -> bool {
+    arg.to_str()
+        .and_then(|arg| arg.strip_prefix('-'))
+        .and_then(|rest| rest.chars().next())
+        .is_some_and(|c| c.is_ascii_digit() || c == '.' || c == '(')
+}
 
-/// Parses a vector of strings representing time durations and returns the total duration.
-/// Each string should be in the format NUMBER[UNIT], where UNIT can be ms, s, m, h, or d.
-#[inline]
-fn parse_duration(arguments: Vec<String>) -> Result<Duration, ParsingError> {
-    let mut duration = Duration::new(0, 0);
+/// The configuration file and the exit codes, as listed at the end of the help.
+const AFTER_HELP: &str = "Configuration:
+  The color options can also be set in $XDG_CONFIG_HOME/snore/config, or ~/.config/snore/config,
+  one `key = value` per line named like the option (e.g., warn-at = 5m). Options given on the
+  command line win. A malformed line or an unknown key in the file is an error on every run,
+  even with --color never.
+
+Exit status:
+  0        the timer ran out, or the stopwatch or overtime was stopped
+  1        a duration, time or the configuration is invalid, or the laps could not be written
+  2        the command line is malformed, such as an unknown option or conflicting options
+  128+N    the timer was cancelled by signal N before it ran out: 130 for Ctrl-C or q,
+           143 for SIGTERM and 129 for SIGHUP";
+
+/// How often prose output may be printed, so screen readers are not flooded.
+const HUMAN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Returns the output that draws one frame: the formatted times joined on one line, or in
+/// large digits, followed by a progress bar filled to `progress` if there is one. The frame
+/// is drawn with the SGR escape sequence `color`, if any.
+fn render_frame(
+    parts: &[String],
+    progress: Option<f64>,
+    color: Option<&str>,
+    options: &Options,
+    unicode: bool,
+) -> String {
+    let (columns, rows) = terminal::size();
+
+    if options.big {
+        let bar = progress.and_then(|fraction| bar::fit(fraction, 0, columns, unicode));
+        return big::frame(parts, bar.as_deref(), color, (columns, rows), unicode);
+    }
 
-    for argument in arguments {
-        let (value, unit) = if let Some(index) = argument.find(char::is_alphabetic) {
-            (&argument[0..index], &argument[index..])
-        } else {
-            (&argument[..], "s")
-        };
+    let (color, reset) = match color {
+        Some(color) => (color, "\x1b[0m"),
+        None => ("", ""),
+    };
 
-        let number = if let Ok(number) = value.parse::<f64>() {
-            number
-        } else {
-            return Err(ParsingError::InvalidNumber);
-        };
+    let mut line = parts.join(" | ");
+    let used = terminal::width(&line);
+    if let Some(bar) = progress.and_then(|fraction| bar::fit(fraction, used, columns, unicode)) {
+        if used > 0 {
+            line.push(' ');
+        }
+        line.push_str(&bar);
+    }
 
-        duration += match unit {
-            "ms" => Duration::from_secs_f64(number / 1000.0),
-            "s" => Duration::from_secs_f64(number),
-            "m" => Duration::from_secs_f64(number * 60.0),
-            "h" => Duration::from_secs_f64(number * 60.0 * 60.0),
-            "d" => Duration::from_secs_f64(number * 60.0 * 60.0 * 24.0),
-            _ => return Err(ParsingError::InvalidUnit),
-        };
+    // Prose goes on a line of its own rather than overwriting the previous one.
+    if options.human {
+        format!("{color}{line}{reset}\n")
+    } else {
+        format!("\x1b[2K\r{color}{line}{reset}")
     }
+}
 
-    Ok(duration)
+/// How the timer ended.
+enum End {
+    /// It ran out, or was stopped in stopwatch or overtime mode, after running this long.
+    Finished(Duration),
+    /// It was cancelled by a signal, or the q key as if by SIGINT, after running this long.
+    Cancelled(libc::c_int, Duration),
 }
 
-/// Formats a `Duration` into a human-readable string.
-fn format_duration(seconds: Duration) -> String {
-    let mut remaining_seconds = seconds.as_secs();
-    let mut remaining_milliseconds = seconds.as_millis();
+/// How much time the `+` and `-` keys add and subtract.
+const MINUTE: Duration = Duration::from_secs(60);
 
-    let days = remaining_seconds / (60 * 60 * 24);
-    remaining_seconds %= 60 * 60 * 24;
+/// Changes made to the terminal while the timer runs, to be undone when it ends.
+struct Session {
+    tui: bool,
+    title: Option<Title>,
+    raw_mode: Option<RawMode>,
+}
 
-    let hours = remaining_seconds / (60 * 60);
-    remaining_seconds %= 60 * 60;
+impl Session {
+    /// Leaves the full-screen display, restores the window title and turns off raw mode,
+    /// where they were used. Only the first call has an effect.
+    fn restore(&mut self) {
+        // Errors are ignored, as this also runs while unwinding from a panic.
+        let mut out = stdout();
 
-    let minutes = remaining_seconds / 60;
-    remaining_seconds %= 60;
+        if std::mem::take(&mut self.tui) {
+            let _ = write!(out, "{}", tui::LEAVE);
+        }
 
-    let seconds = remaining_seconds;
+        if let Some(title) = self.title.take() {
+            let _ = write!(out, "{}", title.restore());
+        }
 
-    remaining_milliseconds %= 1000;
-    let milliseconds = remaining_milliseconds;
+        let _ = out.flush();
 
-    let mut parts = Vec::new();
-    if days > 0 {
-        parts.push(format!("{}d", days));
+        if let Some(raw_mode) = self.raw_mode.take() {
+            raw_mode.restore();
+        }
     }
+}
 
-    parts.push(format!("{hours:02}h {minutes:02}m {seconds:02}s {milliseconds:03}ms"));
-
-    parts.join(" ")
+impl Drop for Session {
+    /// Restores the terminal however `main` is left, including by an error or a panic.
+    fn drop(&mut self) {
+        self.restore();
+    }
 }
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let options = Options::parse();
+    let options = Options::try_parse_args(std::env::args_os()).unwrap_or_else(|error| error.exit());
+    locale::set(options.locale.unwrap_or_else(Locale::from_env));
+    let locale = locale::current();
+
+    let colors = Config::load().and_then(|config| Colors::new(&options.colors, &config));
+    let colors = match colors {
+        Ok(colors) => colors,
+        Err(e) => {
+            eprintln!("{}: {e}", locale.error());
+            process::exit(1);
+        }
+    };
+
+    // A stopwatch is a timer that never ends.
+    let sleep_duration = match &options.until {
+        _ if options.stopwatch => Ok(None),
+        Some(target) => until::parse_until(target).and_then(|target| {
+            let remaining = target.remaining()?;
+            if target.zone.is_some() {
+                println!("{}", locale.waiting_until(&target.describe()));
+            }
+            Ok(Some(remaining))
+        }),
+        None => parse_duration(options.times.clone())
+            .or_else(|error| {
+                if !natural::is_prose(&options.times) {
+                    return Err(error);
+                }
+
+                // Words alone do not make prose, as in "PT" or "inf", so keep the more precise
+                // error unless the input reads like prose from the start.
+                natural::parse_natural(&options.times).map_err(|natural| {
+                    if natural::starts_like_prose(&options.times) {
+                        natural
+                    } else {
+                        error
+                    }
+                })
+            })
+            .map(Some),
+    };
 
-    let sleep_duration = match parse_duration(options.times) {
+    let sleep_duration = match sleep_duration {
         Ok(duration) => duration,
         Err(e) => {
             eprintln!("{e}");
@@ -111,35 +326,362 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
     };
 
-    let start = Instant::now();
+    let style = match &options.format {
+        Some(template) => Style::Template(template.clone()),
+        None if options.print_iso_time => Style::Iso,
+        None if options.human => Style::Human,
+        None => Style::Default(options.precision),
+    };
+    let precision = if options.human {
+        Precision::S
+    } else {
+        options.precision
+    };
+
+    let segments = match (&options.until, sleep_duration) {
+        (_, None) => Vec::new(),
+        (Some(target), Some(duration)) => vec![tui::Segment {
+            label: target.clone(),
+            duration,
+        }],
+        (None, Some(duration)) => tui::segments(&options.times, duration),
+    };
+
+    // The full-screen display shows the remaining time unless told otherwise.
+    let print_descending_time = options.print_descending_time
+        || (options.tui && !options.print_ascending_time && !options.stopwatch);
+    let print_ascending_time = options.print_ascending_time || options.stopwatch;
+
     let tick = Duration::from_millis(10);
+    let unicode = bar::is_unicode();
+    let mut previous_frame = None;
+    let mut painted_at: Option<Instant> = None;
+
+    if options.big || options.bar || options.tui {
+        terminal::watch_resize();
+    }
 
-    loop {
-        let elapsed = start.elapsed();
+    // Titles only make sense on a terminal, and would litter redirected output.
+    let title = (options.title && terminal::is_terminal()).then(|| {
+        let (title, escape) = Title::save();
+        print!("{escape}");
+        title
+    });
+    let title_style = match &style {
+        Style::Default(_) => Style::Default(Precision::S),
+        style => style.clone(),
+    };
+    let mut previous_title = None;
+
+    let mut session = Session {
+        tui: options.tui,
+        title,
+        raw_mode: RawMode::enable(),
+    };
 
-        if elapsed >= sleep_duration {
-            break;
+    // Rather than dying mid-line, clean up and print a summary when cancelled.
+    terminal::watch_signals(&[libc::SIGINT, libc::SIGTERM, libc::SIGHUP]);
+
+    // Laps can be recorded with `kill -USR1` as well as with the l key.
+    terminal::watch_lap();
+
+    if options.tui {
+        print!("{}", tui::ENTER);
+    }
+
+    let hints = if session.raw_mode.is_some() && options.stopwatch {
+        locale.stopwatch_hints()
+    } else if session.raw_mode.is_some() {
+        locale.key_hints()
+    } else {
+        locale.quit_hint()
+    };
+
+    let mut timer = match sleep_duration {
+        Some(duration) => Timer::start(duration),
+        None => Timer::stopwatch(),
+    };
+    let mut last_lap = None;
+
+    let end = loop {
+        let elapsed = timer.elapsed();
+        let duration = timer.duration();
+
+        if let Some(duration) = duration.filter(|&duration| elapsed >= duration) {
+            if !options.overtime {
+                break End::Finished(duration);
+            }
         }
 
-        print!("\x1b[2K\r");
+        // Past zero, the time counts how far over it is instead.
+        let overtime = duration
+            .filter(|&duration| elapsed >= duration)
+            .map(|duration| elapsed - duration);
+
+        if let Some(signal) = terminal::take_signal() {
+            // Stopping is how a stopwatch, or a timer already over, is meant to end.
+            if options.stopwatch || overtime.is_some() {
+                break End::Finished(elapsed);
+            }
+
+            break End::Cancelled(signal, elapsed);
+        }
+
+        // A stopwatch has no time left and makes no progress towards an end.
+        let remaining = duration.map(|duration| duration.saturating_sub(elapsed));
+        let fraction = duration
+            .map(|duration| (elapsed.as_secs_f64() / duration.as_secs_f64()).min(1.0))
+            .unwrap_or_default();
+
+        if let Some(title) = &session.title {
+            let text = if let Some(overtime) = overtime {
+                let text = title_style.format(Precision::S.round(overtime, Rounding::Down));
+                format!("+{text}")
+            } else if let Some(remaining) = remaining {
+                title_style.format(Precision::S.round(remaining, Rounding::Up))
+            } else {
+                title_style.format(Precision::S.round(elapsed, Rounding::Down))
+            };
+
+            // Rounded to seconds, the title changes about once a second.
+            if previous_title.as_ref() != Some(&text) {
+                let _ = write!(stdout(), "{}", title.set(&format!("snore: {text}")));
+                previous_title = Some(text);
+            }
+        }
+
+        let mut parts = Vec::new();
+        if print_ascending_time {
+            let text = style.format(precision.round(elapsed, Rounding::Down));
+            parts.push(if options.human {
+                locale.elapsed(&text)
+            } else {
+                text
+            });
+        }
+        if let (true, Some(overtime)) = (print_descending_time, overtime) {
+            let text = style.format(precision.round(overtime, Rounding::Down));
+            parts.push(if options.human {
+                locale.overtime(&text)
+            } else {
+                format!("+{text}")
+            });
+        } else if let (true, Some(remaining)) = (print_descending_time, remaining) {
+            let text = style.format(precision.round(remaining, Rounding::Up));
+            parts.push(if options.human {
+                locale.remaining(&text)
+            } else {
+                text
+            });
+        }
 
-        if options.print_ascending_time {
-            print!("{}", format_duration(elapsed));
+        // Percentages, end times, pauses and laps go in the status line of the full-screen
+        // display rather than next to the time.
+        let mut extras = Vec::new();
+        if options.print_percentage || (options.tui && !options.stopwatch) {
+            extras.push(format!("{}%", (fraction * 100.0).floor()));
+        }
+        if let (true, Some(ends_at)) = (options.print_end_time || options.tui, timer.ends_at()) {
+            extras.push(locale.ends_at(&until::format_wall_clock(ends_at)));
+        }
+        if !timer.paused().is_zero() {
+            let text = style.format(precision.round(timer.paused(), Rounding::Down));
+            extras.push(locale.paused(&text));
+        }
+        if options.tui || options.big {
+            extras.extend(last_lap.clone());
         }
 
-        if options.print_ascending_time && options.print_descending_time {
-            print!(" | ");
+        let progress = options.bar.then_some(fraction);
+
+        let color = colors.as_ref().and_then(|colors| match overtime {
+            Some(_) => Some(colors.overtime()),
+            None => remaining.and_then(|remaining| colors.escape(remaining)),
+        });
+
+        let frame = if options.tui {
+            let (current, into_current) = tui::current(&segments, elapsed);
+
+            let listed: Vec<(String, String)> = segments
+                .iter()
+                .enumerate()
+                .map(|(index, segment)| {
+                    let duration = if index == current {
+                        precision.round(segment.duration.saturating_sub(into_current), Rounding::Up)
+                    } else {
+                        precision.round(segment.duration, Rounding::Down)
+                    };
+                    (segment.label.clone(), style.format(duration))
+                })
+                .collect();
+
+            if segments.len() > 1 {
+                extras.insert(0, format!("{}/{}", current + 1, segments.len()));
+            }
+
+            let screen = tui::Screen {
+                clock: &parts,
+                color: color.as_deref(),
+                progress: (!options.stopwatch).then_some(fraction),
+                segments: &listed,
+                current,
+                hints,
+                status: &format!(" {}", extras.join(" · ")),
+                unicode,
+            };
+            screen.render(terminal::size())
+        } else {
+            parts.extend(extras);
+            render_frame(&parts, progress, color.as_deref(), &options, unicode)
+        };
+        let throttled = options.human
+            && painted_at.is_some_and(|painted_at| painted_at.elapsed() < HUMAN_INTERVAL);
+
+        let resized = terminal::take_resized();
+        // Only repaint when the output changes, which is rarely at coarse precisions.
+        if (previous_frame.as_ref() != Some(&frame) && !throttled) || resized {
+            if resized && (options.big || options.tui) {
+                let _ = write!(stdout(), "\x1b[2J");
+            }
+            // After a hangup the terminal is gone, and the signal ends the loop soon enough.
+            let _ = write!(stdout(), "{frame}");
+            let _ = stdout().flush();
+            previous_frame = Some(frame);
+            painted_at = Some(Instant::now());
         }
-        if options.print_descending_time {
-            print!("{}", format_duration(sleep_duration - elapsed));
+
+        let key = if session.raw_mode.is_some() {
+            terminal::read_key(tick)
+        } else {
+            sleep(tick);
+            None
+        };
+
+        match key {
+            Some(b' ') => timer.toggle_pause(),
+            Some(b'+') if !options.stopwatch => timer.add(MINUTE),
+            Some(b'-') if !options.stopwatch => timer.subtract(MINUTE),
+            Some(b'r') => {
+                timer.restart();
+                last_lap = None;
+            }
+            Some(b'q') if options.stopwatch || overtime.is_some() => {
+                break End::Finished(timer.elapsed())
+            }
+            Some(b'q') => break End::Cancelled(libc::SIGINT, timer.elapsed()),
+            _ => {}
         }
 
-        stdout().flush()?;
-        sleep(tick);
+        if key == Some(b'l') || terminal::take_lap() {
+            let (number, lap) = timer.lap();
+            let text = locale.lap(number, &style.format(precision.round(lap, Rounding::Down)));
+
+            if options.tui || options.big {
+                last_lap = Some(text);
+            } else {
+                // Laps scroll up above the line that is repainted.
+                let _ = write!(stdout(), "\x1b[2K\r{text}\n");
+                previous_frame = None;
+            }
+        }
+    };
+
+    session.restore();
+
+    // Writing fails once the terminal has hung up, which must not stop the exit status below.
+    let mut out = stdout();
+
+    match end {
+        End::Finished(total) => {
+            let text = style.format(precision.round(total, Rounding::Down));
+            let progress = options.bar.then_some(1.0);
+            let _ = write!(
+                out,
+                "{}",
+                render_frame(&[text], progress, None, &options, unicode)
+            );
+
+            if options.big {
+                let (_, rows) = terminal::size();
+                let _ = writeln!(out, "\x1b[{rows};1H");
+            } else if !options.human {
+                let _ = writeln!(out);
+            }
+
+            if options.overtime {
+                let overtime = total.saturating_sub(timer.duration().unwrap_or(total));
+                let text = style.format(precision.round(overtime, Rounding::Down));
+                let _ = writeln!(out, "{}", locale.overtime(&text));
+            }
+        }
+        End::Cancelled(_, elapsed) => {
+            // Clear the half-drawn time, which the full-screen display already took along.
+            if options.big {
+                let _ = write!(out, "\x1b[H\x1b[2J");
+            } else if !options.human && !options.tui {
+                let _ = write!(out, "\x1b[2K\r");
+            }
+
+            let remaining = timer
+                .duration()
+                .map(|duration| duration.saturating_sub(elapsed));
+            let elapsed = format_duration(precision.round(elapsed, Rounding::Down), precision);
+            let _ = writeln!(out, "{}", locale.elapsed(&elapsed));
+
+            if let Some(remaining) = remaining {
+                let remaining =
+                    format_duration(precision.round(remaining, Rounding::Up), precision);
+                let _ = writeln!(out, "{}", locale.remaining(&remaining));
+            }
+        }
+    }
+
+    let laps = laps::laps(timer.laps());
+    if !laps.is_empty() {
+        let _ = writeln!(out, "{}", laps::table(&laps, precision));
     }
 
-    print!("\x1b[2K\r");
-    println!("{}", format_duration(sleep_duration));
+    if let Some(export) = &options.export_laps {
+        if let Err(e) = export.write(&laps, precision) {
+            eprintln!("{}: {e}", locale.error());
+            process::exit(1);
+        }
+    }
+
+    if let End::Cancelled(signal, _) = end {
+        let _ = out.flush();
+        process::exit(128 + signal);
+    }
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn times(args: &[&str]) -> Vec<String> {
+        let args = std::iter::once("snore").chain(args.iter().copied());
+        Options::try_parse_args(args).unwrap().times
+    }
+
+    #[test]
+    fn reads_durations_that_start_with_a_minus_sign() {
+        assert_eq!(times(&["2h", "-15m"]), ["2h", "-15m"]);
+        assert_eq!(times(&["-5m+1h"]), ["-5m+1h"]);
+        assert_eq!(times(&["1h", "-(5m+1m)"]), ["1h", "-(5m+1m)"]);
+    }
+
+    #[test]
+    fn reads_options_after_durations() {
+        let options = Options::try_parse_args(["snore", "5m", "-d", "--big"]).unwrap();
+        assert_eq!(options.times, ["5m"]);
+        assert!(options.print_descending_time && options.big);
+    }
+
+    #[test]
+    fn reports_unknown_options() {
+        let error = Options::try_parse_args(["snore", "5m", "--bigg"]).unwrap_err();
+        assert_eq!(error.kind(), clap::error::ErrorKind::UnknownArgument);
+    }
+}
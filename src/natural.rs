@@ -0,0 +1,584 @@
+use std::time::{Duration, SystemTime};
+
+use crate::parse::{
+    edit_distance, parse_duration, to_duration, unit_seconds, ErrorKind, ParsingError, UNITS,
+};
+use crate::until;
+
+/// A lowercased word of the input together with its byte range.
+#[derive(Debug)]
+struct Word {
+    text: String,
+    start: usize,
+    end: usize,
+}
+
+/// Returns whether the arguments read like prose rather than NUMBER[UNIT] durations,
+/// that is, whether any of their words consists of letters only.
+pub fn is_prose(arguments: &[String]) -> bool {
+    arguments
+        .iter()
+        .flat_map(|argument| argument.split_whitespace())
+        .any(|word| word.chars().all(|c| c.is_alphabetic() || c == '\''))
+}
+
+/// Returns whether the arguments start like prose, with a word the parser knows such as "in",
+/// "an" or "tomorrow" or a number written as a word, so that its errors are worth showing
+/// rather than those of `parse_duration`.
+pub fn starts_like_prose(arguments: &[String]) -> bool {
+    let text = arguments.join(" ");
+    split_words(&text).first().is_some_and(|word| {
+        KEYWORDS.contains(&word.text.as_str()) || number_word(&word.text).is_some()
+    })
+}
+
+/// Words the parser matches, besides numbers and units.
+const KEYWORDS: [&str; 26] = [
+    "in", "for", "after", "wait", "and", "plus", "a", "an", "of", "half", "quarter", "quarters",
+    "couple", "from", "now", "later", "today", "tomorrow", "tonight", "at", "noon", "midday",
+    "midnight", "o'clock", "am", "pm",
+];
+
+/// Numbers from zero to nineteen, written as English words.
+const ONES: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+/// Multiples of ten from twenty to ninety, written as English words.
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Parses a natural-language duration such as "in 2 hours and 15 minutes", "half an hour"
+/// or "tomorrow at noon", given as one or more arguments.
+pub fn parse_natural(arguments: &[String]) -> Result<Duration, ParsingError> {
+    let text = arguments.join(" ");
+    parse_text(&text, SystemTime::now()).map_err(|error| place(error, arguments))
+}
+
+/// Moves the location of an error from the joined text into the argument it falls in.
+fn place(mut error: ParsingError, arguments: &[String]) -> ParsingError {
+    let Some(location) = &mut error.location else {
+        return error;
+    };
+
+    let mut offset = 0;
+    for (index, argument) in arguments.iter().enumerate() {
+        if location.start <= offset + argument.len() || index + 1 == arguments.len() {
+            location.argument = index;
+            location.source = argument.clone();
+            location.start -= offset;
+            location.end = (location.end - offset).min(argument.len());
+            break;
+        }
+
+        offset += argument.len() + 1;
+    }
+
+    error
+}
+
+/// Suggests the known word closest to a misspelled one. A known word in the wrong place is
+/// not misspelled, so it gets no suggestion.
+fn suggest_word(word: &str) -> Option<&'static str> {
+    let limit = (word.chars().count() / 3).max(1);
+
+    let units = UNITS.iter().map(|&(unit, _)| unit);
+    let vocabulary = KEYWORDS.into_iter().chain(ONES).chain(TENS).chain(units);
+
+    if vocabulary.clone().any(|known| known == word) {
+        return None;
+    }
+
+    vocabulary
+        .map(|candidate| (edit_distance(candidate, word), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Parses the joined arguments, with times of day counted from `now`.
+fn parse_text(text: &str, now: SystemTime) -> Result<Duration, ParsingError> {
+    let mut parser = Parser {
+        text,
+        words: split_words(text),
+        index: 0,
+        now,
+    };
+
+    let is_relative = parser.skip(&["in", "for", "after", "wait"]);
+
+    if parser.is_time_of_day(is_relative) {
+        return parser.time_of_day();
+    }
+
+    let seconds = parser.relative()?;
+    to_duration(seconds).map_err(|kind| ParsingError::at(kind, 0, text.len()))
+}
+
+/// Splits text into lowercased words at whitespace, commas and hyphens, and between runs of
+/// digits and letters, so that "5pm" becomes "5" and "pm". A minus sign in front of a number
+/// stays with it, so that "-5" can be rejected as negative rather than read as "5".
+fn split_words(text: &str) -> Vec<Word> {
+    #[derive(PartialEq)]
+    enum Class {
+        Number,
+        Letter,
+        Other,
+    }
+
+    let class = |c: char| {
+        if c.is_ascii_digit() || c == '.' || c == ':' {
+            Class::Number
+        } else if c.is_alphabetic() || c == '\'' {
+            Class::Letter
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut words: Vec<Word> = Vec::new();
+    let mut previous = None;
+
+    for (index, c) in text.char_indices() {
+        let is_minus = c == '-'
+            && previous != Some(Class::Number)
+            && text[index + 1..].starts_with(|c: char| c.is_ascii_digit());
+
+        if c.is_whitespace() || c == ',' || (c == '-' && !is_minus) {
+            previous = None;
+            continue;
+        }
+
+        let current = if is_minus { Class::Number } else { class(c) };
+        match words.last_mut() {
+            Some(word) if previous.as_ref() == Some(&current) => {
+                word.text.extend(c.to_lowercase());
+                word.end = index + c.len_utf8();
+            }
+            _ => words.push(Word {
+                text: c.to_lowercase().collect(),
+                start: index,
+                end: index + c.len_utf8(),
+            }),
+        }
+
+        previous = Some(current);
+    }
+
+    words
+}
+
+/// A recursive-descent parser over the words of a natural-language duration.
+struct Parser<'a> {
+    text: &'a str,
+    words: Vec<Word>,
+    index: usize,
+    /// The time that times of day are counted from.
+    now: SystemTime,
+}
+
+impl Parser<'_> {
+    /// Returns the word `offset` positions ahead of the current one.
+    fn peek(&self, offset: usize) -> Option<&str> {
+        self.words
+            .get(self.index + offset)
+            .map(|word| word.text.as_str())
+    }
+
+    /// Consumes the current word if it is one of `words`.
+    fn skip(&mut self, words: &[&str]) -> bool {
+        let matches = self.peek(0).is_some_and(|word| words.contains(&word));
+        if matches {
+            self.index += 1;
+        }
+
+        matches
+    }
+
+    /// Returns an error pointing at the current word, or at the end of the input.
+    fn error(&self) -> ParsingError {
+        let Some(word) = self.words.get(self.index) else {
+            return ParsingError::at(ErrorKind::UnexpectedEnd, self.text.len(), self.text.len());
+        };
+
+        let error = ParsingError::at(ErrorKind::UnknownWord, word.start, word.end);
+        match suggest_word(&word.text) {
+            Some(suggestion) => error.suggest(suggestion.to_string()),
+            None => error,
+        }
+    }
+
+    /// Parses a sum of amounts, such as "2 hours and 15 minutes".
+    fn relative(&mut self) -> Result<f64, ParsingError> {
+        let mut seconds = self.amount()?;
+
+        while self.peek(0).is_some() {
+            if self.peek(0) == Some("from") && self.peek(1) == Some("now") {
+                self.index += 2;
+                break;
+            }
+
+            if self.skip(&["later"]) {
+                break;
+            }
+
+            self.skip(&["and", "plus"]);
+            seconds += self.amount()?;
+        }
+
+        if self.peek(0).is_some() {
+            return Err(self.error());
+        }
+
+        Ok(seconds)
+    }
+
+    /// Parses a single amount, such as "an hour and a half", "three quarters of an hour" or
+    /// "1:30", which is read like `parse_duration` reads it.
+    fn amount(&mut self) -> Result<f64, ParsingError> {
+        if let Some(word) = self
+            .words
+            .get(self.index)
+            .filter(|word| word.text.contains(':'))
+        {
+            let (start, end) = (word.start, word.end);
+            let duration = parse_duration(vec![word.text.clone()])
+                .map_err(|error| ParsingError::at(error.kind, start, end))?;
+
+            self.index += 1;
+            return Ok(duration.as_secs_f64());
+        }
+
+        let mut count = self.count();
+
+        if let Some(fraction) = self.and_a_fraction() {
+            count = Some(count.unwrap_or(1.0) + fraction);
+        }
+
+        let fraction = match self.peek(0) {
+            Some("half") => Some(0.5),
+            Some("quarter" | "quarters") => Some(0.25),
+            _ => None,
+        };
+        if let Some(fraction) = fraction {
+            self.index += 1;
+            count = Some(count.unwrap_or(1.0) * fraction);
+            self.skip(&["of"]);
+            self.skip(&["a", "an"]);
+        }
+
+        let scale = match self.peek(0).and_then(unit_seconds) {
+            Some(scale) => scale,
+            None => return Err(self.error()),
+        };
+        self.index += 1;
+
+        let mut seconds = count.unwrap_or(1.0) * scale;
+        if let Some(fraction) = self.and_a_fraction() {
+            seconds += fraction * scale;
+        }
+
+        Ok(seconds)
+    }
+
+    /// Parses a count, written in digits, in words, or as "a", "an" or "a couple of".
+    fn count(&mut self) -> Option<f64> {
+        let word = self.peek(0)?.to_string();
+
+        // Only digits count, as `f64` would also accept words like "inf" and "nan".
+        let digits = word.strip_prefix('-').unwrap_or(&word);
+        let is_number = digits.starts_with(|c: char| c.is_ascii_digit()) && !word.contains(':');
+        if let Some(number) = word.parse::<f64>().ok().filter(|_| is_number) {
+            self.index += 1;
+            return Some(number);
+        }
+
+        if word == "a" || word == "an" {
+            self.index += 1;
+            if self.skip(&["couple"]) {
+                self.skip(&["of"]);
+                return Some(2.0);
+            }
+            return Some(1.0);
+        }
+
+        if self.skip(&["couple"]) {
+            self.skip(&["of"]);
+            return Some(2.0);
+        }
+
+        let mut number = number_word(&word)?;
+        self.index += 1;
+
+        if number >= 20 && number % 10 == 0 {
+            if let Some(ones) = self.peek(0).and_then(number_word).filter(|ones| *ones < 10) {
+                self.index += 1;
+                number += ones;
+            }
+        }
+
+        Some(number as f64)
+    }
+
+    /// Parses a trailing "and a half" or "and a quarter".
+    fn and_a_fraction(&mut self) -> Option<f64> {
+        if self.peek(0) != Some("and") || !matches!(self.peek(1), Some("a" | "an")) {
+            return None;
+        }
+
+        let fraction = match self.peek(2) {
+            Some("half") => 0.5,
+            Some("quarter") => 0.25,
+            _ => return None,
+        };
+
+        self.index += 3;
+        Some(fraction)
+    }
+
+    /// Returns whether the remaining words name a time of day rather than an amount of time.
+    /// After "in", "for" or "after", a clock word such as "1:30" is an amount of time.
+    fn is_time_of_day(&self, is_relative: bool) -> bool {
+        match self.peek(0) {
+            Some("today" | "tomorrow" | "tonight" | "at" | "noon" | "midday" | "midnight") => true,
+            Some(word) => {
+                (word.contains(':') && !is_relative)
+                    || matches!(self.peek(1), Some("am" | "pm" | "o'clock"))
+            }
+            None => false,
+        }
+    }
+
+    /// Parses a time of day, such as "tomorrow at noon" or "at 5:30 pm", and returns how long
+    /// it is until then.
+    fn time_of_day(&mut self) -> Result<Duration, ParsingError> {
+        let mut days = None;
+        let mut time = None;
+        let mut is_evening = false;
+        let mut is_midnight = false;
+        // Only "tomorrow" makes sense on its own, as a whole day from now.
+        let mut needs_time = false;
+
+        while let Some(word) = self.peek(0).map(str::to_string) {
+            match word.as_str() {
+                "today" | "tomorrow" | "tonight" if days.is_none() => {
+                    days = Some(if word == "tomorrow" { 1 } else { 0 });
+                    is_evening = word == "tonight";
+                    needs_time |= word != "tomorrow";
+                    self.index += 1;
+                }
+                "at" => {
+                    needs_time = true;
+                    self.index += 1;
+                }
+                "noon" | "midday" if time.is_none() => {
+                    time = Some((12, 0));
+                    self.index += 1;
+                }
+                "midnight" if time.is_none() => {
+                    time = Some((0, 0));
+                    is_midnight = true;
+                    self.index += 1;
+                }
+                _ if time.is_none() => time = Some(self.clock(is_evening)?),
+                _ => return Err(self.error()),
+            }
+        }
+
+        // Midnight ends the day it belongs to, so "today at midnight" is the start of tomorrow.
+        if is_midnight {
+            days = days.map(|days| days + 1);
+        }
+
+        match time {
+            Some((hour, minute)) => until::until_time_of_day(days, hour, minute, self.now),
+            None if needs_time => Err(self.error()),
+            None => Ok(Duration::from_secs(60 * 60 * 24 * days.unwrap_or(0) as u64)),
+        }
+    }
+
+    /// Parses a clock time such as "5", "17:30", "5:30 pm" or "9 o'clock".
+    fn clock(&mut self, is_evening: bool) -> Result<(u32, u32), ParsingError> {
+        let word = self.peek(0).unwrap_or_default();
+        let (hour, minute) = word.split_once(':').unwrap_or((word, "00"));
+        let minute_len = minute.len();
+
+        let (hour, minute) = match (hour.parse::<u32>(), minute.parse::<u32>()) {
+            (Ok(hour), Ok(minute)) if hour <= 23 && minute <= 59 && minute_len == 2 => {
+                (hour, minute)
+            }
+            _ => return Err(self.error()),
+        };
+        self.index += 1;
+
+        let hour = match self.peek(0) {
+            Some("am") if (1..=12).contains(&hour) => {
+                self.index += 1;
+                hour % 12
+            }
+            Some("pm") if (1..=12).contains(&hour) => {
+                self.index += 1;
+                hour % 12 + 12
+            }
+            Some("am" | "pm") => return Err(self.error()),
+            _ if is_evening && (1..12).contains(&hour) => hour + 12,
+            _ => hour,
+        };
+
+        self.skip(&["o'clock"]);
+        Ok((hour, minute))
+    }
+}
+
+/// Returns the value of a number written as an English word, from zero to ninety.
+fn number_word(word: &str) -> Option<u32> {
+    if let Some(index) = ONES.iter().position(|&ones| ones == word) {
+        return Some(index as u32);
+    }
+
+    TENS.iter()
+        .position(|&tens| tens == word)
+        .map(|index| (index as u32 + 2) * 10)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::time::UNIX_EPOCH;
+
+    /// 2024-05-01 10:00:00 UTC, with no clock changes around it in most zones, so that times
+    /// of day can be compared without knowing the local zone.
+    fn now() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_714_557_600)
+    }
+
+    fn seconds(text: &str) -> f64 {
+        parse_text(text, now()).unwrap().as_secs_f64()
+    }
+
+    fn error(text: &str) -> ErrorKind {
+        parse_text(text, now()).unwrap_err().kind
+    }
+
+    #[test]
+    fn parses_amounts_of_time() {
+        assert_eq!(seconds("in 2 hours and 15 minutes"), 8_100.0);
+        assert_eq!(seconds("an hour and a half"), 5_400.0);
+        assert_eq!(seconds("three quarters of an hour"), 2_700.0);
+        assert_eq!(seconds("half an hour"), 1_800.0);
+        assert_eq!(seconds("a couple of minutes"), 120.0);
+        assert_eq!(seconds("twenty-five minutes"), 1_500.0);
+        assert_eq!(seconds("for 5 minutes plus 30 seconds"), 330.0);
+        assert_eq!(seconds("10 minutes from now"), 600.0);
+        assert_eq!(seconds("wait 2 days later"), 172_800.0);
+        assert_eq!(seconds("tomorrow"), 86_400.0);
+    }
+
+    #[test]
+    fn reads_clock_words_after_in_as_amounts() {
+        assert_eq!(seconds("in 1:30"), 90.0);
+        assert_eq!(seconds("for 1:30:00"), 5_400.0);
+        assert_eq!(seconds("after 1:30 and 10 seconds"), 100.0);
+        assert_eq!(error("in 1:75"), ErrorKind::MalformedClock);
+    }
+
+    #[test]
+    fn parses_times_of_day() {
+        let five_pm = seconds("at 17:00");
+        assert!(five_pm < 86_400.0);
+        assert_eq!(seconds("at 5pm"), five_pm);
+        assert_eq!(seconds("5 pm"), five_pm);
+        assert_eq!(seconds("17:00"), five_pm);
+        assert_eq!(seconds("at 1:30"), seconds("1:30 am"));
+        assert_eq!(seconds("at noon"), seconds("12 o'clock"));
+        assert_eq!(seconds("today at midnight"), seconds("tomorrow at 0:00"));
+        assert_eq!(
+            seconds("tomorrow at 12:30") - seconds("tomorrow at noon"),
+            1_800.0
+        );
+
+        // Whether nine in the evening is still to come today depends on the local zone.
+        match parse_text("tonight at 9", now()) {
+            Ok(duration) => assert_eq!(duration.as_secs_f64(), seconds("at 21:00")),
+            Err(error) => assert_eq!(error.kind, ErrorKind::PastTarget),
+        }
+    }
+
+    #[test]
+    fn rejects_days_without_a_time() {
+        assert_eq!(error("today"), ErrorKind::UnexpectedEnd);
+        assert_eq!(error("tonight"), ErrorKind::UnexpectedEnd);
+        assert_eq!(error("tomorrow at"), ErrorKind::UnexpectedEnd);
+    }
+
+    #[test]
+    fn rejects_negative_amounts() {
+        assert_eq!(error("in -5 minutes"), ErrorKind::Negative);
+        assert_eq!(seconds("an hour and -5 minutes"), 3_300.0);
+    }
+
+    #[test]
+    fn rejects_impossible_times_and_unknown_words() {
+        assert_eq!(error("at 24:00"), ErrorKind::UnknownWord);
+        assert_eq!(error("13 pm"), ErrorKind::UnknownWord);
+        assert_eq!(error("in 2 hours and"), ErrorKind::UnexpectedEnd);
+
+        let typo = parse_text("in 2 hourz", now()).unwrap_err();
+        assert_eq!(typo.kind, ErrorKind::UnknownWord);
+        assert_eq!(typo.suggestion.as_deref(), Some("hour"));
+    }
+
+    #[test]
+    fn suggests_only_for_misspelled_words() {
+        assert_eq!(suggest_word("tenn"), Some("ten"));
+        assert_eq!(suggest_word("wekks"), Some("weeks"));
+        assert_eq!(suggest_word("pm"), None);
+        assert_eq!(suggest_word("xyzzy"), None);
+
+        let misplaced = parse_text("13 pm", now()).unwrap_err();
+        assert_eq!(misplaced.suggestion, None);
+    }
+
+    #[test]
+    fn places_errors_in_their_argument() {
+        let arguments = ["in", "2", "hourz"].map(String::from);
+        let error = parse_natural(&arguments).unwrap_err();
+        let location = error.location.unwrap();
+        assert_eq!((location.argument, location.text()), (2, "hourz"));
+    }
+
+    #[test]
+    fn tells_prose_from_durations() {
+        let prose = |text: &str| {
+            let arguments = [text.to_string()];
+            (is_prose(&arguments), starts_like_prose(&arguments))
+        };
+
+        assert_eq!(prose("in 5 minutes"), (true, true));
+        assert_eq!(prose("twenty minutes"), (true, true));
+        assert_eq!(prose("5 minutes"), (true, false));
+        assert_eq!(prose("PT"), (true, false));
+        assert_eq!(prose("1h30m"), (false, false));
+    }
+}
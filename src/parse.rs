@@ -0,0 +1,917 @@
+use std::time::Duration;
+
+use crate::locale;
+
+/// The longest duration snore accepts: one hundred years of 365 days. Anything longer is
+/// rejected with `ErrorKind::Overflow` rather than risking overflowing `Instant` arithmetic.
+pub const MAX_DURATION: Duration = Duration::from_secs(100 * 365 * 24 * 60 * 60);
+
+/// The reason a duration or target time could not be parsed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    InvalidNumber,
+    InvalidUnit,
+    MalformedClock,
+    MalformedIso8601,
+    MalformedTime,
+    MalformedDate,
+    PastTarget,
+    UnknownTimeZone,
+    MalformedExpression,
+    DivisionByZero,
+    Negative,
+    NotFinite,
+    Overflow,
+    UnknownWord,
+    UnexpectedEnd,
+}
+
+/// An error raised while parsing the command line, with the location of the offending text
+/// and a suggested correction, where known.
+#[derive(Debug)]
+pub struct ParsingError {
+    pub kind: ErrorKind,
+    pub location: Option<Location>,
+    pub suggestion: Option<String>,
+}
+
+/// Where in the command-line arguments a parsing error occurred.
+#[derive(Debug, Clone)]
+pub struct Location {
+    /// Index of the argument the error occurred in.
+    pub argument: usize,
+    /// The full text of that argument.
+    pub source: String,
+    /// Byte range of the offending text within the argument.
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Location {
+    /// Returns the offending text.
+    pub fn text(&self) -> &str {
+        &self.source[self.start..self.end]
+    }
+}
+
+impl ParsingError {
+    /// Creates an error pointing at `start..end` of the text being parsed. The location is
+    /// relative until `locate` places it within an argument.
+    pub fn at(kind: ErrorKind, start: usize, end: usize) -> ParsingError {
+        ParsingError {
+            kind,
+            location: Some(Location {
+                argument: 0,
+                source: String::new(),
+                start,
+                end,
+            }),
+            suggestion: None,
+        }
+    }
+
+    /// Attaches a suggested correction for the offending text.
+    pub fn suggest(mut self, suggestion: String) -> ParsingError {
+        self.suggestion = Some(suggestion);
+        self
+    }
+
+    /// Places an error raised while parsing `source[offset..offset + length]` within that
+    /// argument. Errors without a location of their own cover the whole piece.
+    pub fn locate(mut self, argument: usize, source: &str, offset: usize, length: usize) -> Self {
+        let (start, end) = match &self.location {
+            Some(location) => (offset + location.start, offset + location.end),
+            None => (offset, offset + length),
+        };
+
+        self.location = Some(Location {
+            argument,
+            source: source.to_string(),
+            start,
+            end,
+        });
+        self
+    }
+}
+
+impl From<ErrorKind> for ParsingError {
+    fn from(kind: ErrorKind) -> ParsingError {
+        ParsingError {
+            kind,
+            location: None,
+            suggestion: None,
+        }
+    }
+}
+
+impl std::fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", locale::current().error_message(*self))
+    }
+}
+
+/// Renders the error like rustc does, underlining the offending text of the argument with
+/// carets and appending the suggestion, if any, in the current locale:
+///
+/// ```text
+/// Error: Invalid unit format
+///  --> argument 1, column 2
+///   |
+/// 1 | 5mn
+///   |  ^^ help: did you mean `5m`?
+/// ```
+impl std::fmt::Display for ParsingError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let locale = locale::current();
+        let Some(location) = &self.location else {
+            return write!(f, "{}: {}", locale.error(), self.kind);
+        };
+
+        if self.kind == ErrorKind::UnknownWord {
+            let message = locale.could_not_understand(location.text());
+            write!(f, "{}: {message}", locale.error())?;
+        } else {
+            write!(f, "{}: {}", locale.error(), self.kind)?;
+        }
+
+        let number = (location.argument + 1).to_string();
+        let gutter = " ".repeat(number.len());
+        let column = location.source[..location.start].chars().count();
+        let width = location.text().chars().count().max(1);
+
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{gutter}--> {}",
+            locale.position(location.argument + 1, column + 1)
+        )?;
+        writeln!(f, "{gutter} |")?;
+        writeln!(f, "{number} | {}", location.source)?;
+        write!(f, "{gutter} | {}{}", " ".repeat(column), "^".repeat(width))?;
+
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " {}", locale.did_you_mean(suggestion))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for ParsingError {}
+
+/// Parses a vector of strings representing time durations and returns the total duration.
+/// Each argument holds one or more quantities, which may be combined with `+`, `-`, `*`, `/`
+/// and parentheses (e.g., `2h-15m`, `(25m+5m)*4`, `1h/3`). Quantities written next to each other,
+/// within an argument or across arguments, are added up.
+///
+/// A quantity is one or more NUMBER[UNIT] pairs (e.g., 10s, 1h30m15s), where UNIT can be ns,
+/// us, ms, s, m, h, d or w, or one of their aliases such as sec, mins or hours, in any case.
+/// A lone NUMBER without a unit is taken as seconds, or as a plain factor when it
+/// multiplies or divides a duration. Clock-style quantities ([[DD:]HH:]MM:SS[.FFF]) and ISO 8601
+/// durations (PnYnMnWnDTnHnMnS) are accepted as well.
+#[inline]
+pub fn parse_duration(arguments: Vec<String>) -> Result<Duration, ParsingError> {
+    let mut tokens = Vec::new();
+    for (index, argument) in arguments.iter().enumerate() {
+        tokenize(index, argument, &mut tokens)?;
+    }
+
+    let mut parser = Parser {
+        arguments: &arguments,
+        tokens,
+        index: 0,
+    };
+
+    let value = parser.sum()?;
+    if parser.index < parser.tokens.len() {
+        return Err(parser.error(ErrorKind::MalformedExpression, parser.index, parser.index));
+    }
+
+    to_duration(value.seconds()).map_err(|kind| parser.error(kind, 0, parser.tokens.len() - 1))
+}
+
+/// Converts seconds into a `Duration`, rejecting values that are not finite, negative or longer
+/// than `MAX_DURATION` instead of panicking like `Duration::from_secs_f64`.
+pub fn to_duration(seconds: f64) -> Result<Duration, ErrorKind> {
+    if !seconds.is_finite() {
+        return Err(ErrorKind::NotFinite);
+    }
+
+    if seconds < 0.0 {
+        return Err(ErrorKind::Negative);
+    }
+
+    if seconds > MAX_DURATION.as_secs_f64() {
+        return Err(ErrorKind::Overflow);
+    }
+
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+/// A lexical element of a duration expression.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Token {
+    Quantity(Value),
+    Plus,
+    Minus,
+    Star,
+    Slash,
+    Open,
+    Close,
+}
+
+/// A token together with the argument and byte range it was read from.
+#[derive(Debug, Clone, Copy)]
+struct Spanned {
+    token: Token,
+    argument: usize,
+    start: usize,
+    end: usize,
+}
+
+/// The value of a (sub)expression: either a duration in seconds or a plain number.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Value {
+    Duration(f64),
+    Number(f64),
+}
+
+impl Value {
+    /// Returns the value in seconds, taking plain numbers as seconds.
+    fn seconds(self) -> f64 {
+        match self {
+            Value::Duration(seconds) | Value::Number(seconds) => seconds,
+        }
+    }
+}
+
+/// Splits an argument into operators and quantities and appends them to `tokens`.
+fn tokenize(index: usize, argument: &str, tokens: &mut Vec<Spanned>) -> Result<(), ParsingError> {
+    const OPERATORS: [char; 6] = ['+', '-', '*', '/', '(', ')'];
+
+    if argument.trim().is_empty() {
+        return Err(ParsingError::from(ErrorKind::InvalidNumber).locate(index, argument, 0, 0));
+    }
+
+    let mut start = 0;
+
+    while let Some(c) = argument[start..].chars().next() {
+        if c.is_whitespace() {
+            start += c.len_utf8();
+            continue;
+        }
+
+        let (token, length) = if OPERATORS.contains(&c) {
+            let token = match c {
+                '+' => Token::Plus,
+                '-' => Token::Minus,
+                '*' => Token::Star,
+                '/' => Token::Slash,
+                '(' => Token::Open,
+                _ => Token::Close,
+            };
+            (token, 1)
+        } else {
+            let length = argument[start..]
+                .find(|c: char| c.is_whitespace() || OPERATORS.contains(&c))
+                .unwrap_or(argument.len() - start);
+            let quantity = &argument[start..start + length];
+
+            let value = parse_quantity(quantity)
+                .and_then(|value| checked(value).map_err(ParsingError::from))
+                .map_err(|error| error.locate(index, argument, start, length))?;
+            (Token::Quantity(value), length)
+        };
+
+        tokens.push(Spanned {
+            token,
+            argument: index,
+            start,
+            end: start + length,
+        });
+        start += length;
+    }
+
+    Ok(())
+}
+
+/// A recursive-descent parser over the tokens of a duration expression.
+struct Parser<'a> {
+    arguments: &'a [String],
+    tokens: Vec<Spanned>,
+    index: usize,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<Token> {
+        self.tokens.get(self.index).map(|spanned| spanned.token)
+    }
+
+    /// Returns an error covering the tokens `first..=last`, or only the first one if they span
+    /// several arguments. An index past the end points just behind the last token.
+    fn error(&self, kind: ErrorKind, first: usize, last: usize) -> ParsingError {
+        let Some(end_token) = self.tokens.last() else {
+            return kind.into();
+        };
+
+        let (argument, start) = match self.tokens.get(first) {
+            Some(token) => (token.argument, token.start),
+            None => (end_token.argument, end_token.end),
+        };
+
+        let end = match self.tokens.get(last) {
+            Some(token) if token.argument == argument && first < self.tokens.len() => token.end,
+            _ => self.tokens.get(first).map_or(start, |token| token.end),
+        };
+
+        ParsingError::at(kind, 0, end - start).locate(
+            argument,
+            &self.arguments[argument],
+            start,
+            end - start,
+        )
+    }
+
+    /// Parses `product (('+' | '-')? product)*`, where a missing operator means addition.
+    fn sum(&mut self) -> Result<Value, ParsingError> {
+        let mut value = self.product()?;
+
+        loop {
+            let negate = match self.peek() {
+                Some(Token::Plus) => false,
+                Some(Token::Minus) => true,
+                Some(Token::Quantity(_) | Token::Open) => false,
+                _ => return Ok(value),
+            };
+
+            if matches!(self.peek(), Some(Token::Plus | Token::Minus)) {
+                self.index += 1;
+            }
+
+            let first = self.index;
+            let operand = self.product()?;
+            let last = self.index - 1;
+
+            let operand = if negate {
+                negate_value(operand)
+            } else {
+                operand
+            };
+            value = checked(add(value, operand)).map_err(|kind| self.error(kind, first, last))?;
+        }
+    }
+
+    /// Parses `unary (('*' | '/') unary)*`.
+    fn product(&mut self) -> Result<Value, ParsingError> {
+        let mut value = self.unary()?;
+
+        loop {
+            let operator = match self.peek() {
+                Some(operator @ (Token::Star | Token::Slash)) => operator,
+                _ => return Ok(value),
+            };
+
+            let position = self.index;
+            self.index += 1;
+
+            let first = self.index;
+            let operand = self.unary()?;
+            let last = self.index - 1;
+
+            value = match operator {
+                Token::Star => multiply(value, operand),
+                _ => divide(value, operand),
+            }
+            .and_then(checked)
+            .map_err(|kind| match kind {
+                ErrorKind::DivisionByZero => self.error(kind, first, last),
+                _ => self.error(kind, position, position),
+            })?;
+        }
+    }
+
+    /// Parses `('-' | '+') unary | quantity | '(' sum ')'`.
+    fn unary(&mut self) -> Result<Value, ParsingError> {
+        let position = self.index;
+        let token = self
+            .peek()
+            .ok_or_else(|| self.error(ErrorKind::UnexpectedEnd, position, position))?;
+        self.index += 1;
+
+        match token {
+            Token::Minus => Ok(negate_value(self.unary()?)),
+            Token::Plus => self.unary(),
+            Token::Quantity(value) => Ok(value),
+            Token::Open => {
+                let value = self.sum()?;
+                match self.peek() {
+                    Some(Token::Close) => {
+                        self.index += 1;
+                        Ok(value)
+                    }
+                    Some(_) => {
+                        Err(self.error(ErrorKind::MalformedExpression, self.index, self.index))
+                    }
+                    None => {
+                        let argument = self.tokens[self.index - 1].argument;
+                        Err(self
+                            .error(ErrorKind::UnexpectedEnd, self.index, self.index)
+                            .suggest(format!("{})", self.arguments[argument])))
+                    }
+                }
+            }
+            _ => Err(self.error(ErrorKind::MalformedExpression, position, position)),
+        }
+    }
+}
+
+/// Adds two values; a plain number added to a duration counts as seconds.
+fn add(left: Value, right: Value) -> Value {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => Value::Number(left + right),
+        _ => Value::Duration(left.seconds() + right.seconds()),
+    }
+}
+
+/// Checks the result of an operation against `MAX_DURATION`, so that the running result never
+/// silently overflows into infinity.
+fn checked(value: Value) -> Result<Value, ErrorKind> {
+    match value {
+        Value::Duration(seconds) if seconds.abs() > MAX_DURATION.as_secs_f64() => {
+            Err(ErrorKind::Overflow)
+        }
+        Value::Duration(seconds) | Value::Number(seconds) if !seconds.is_finite() => {
+            Err(ErrorKind::Overflow)
+        }
+        _ => Ok(value),
+    }
+}
+
+fn negate_value(value: Value) -> Value {
+    match value {
+        Value::Duration(seconds) => Value::Duration(-seconds),
+        Value::Number(number) => Value::Number(-number),
+    }
+}
+
+/// Multiplies two values; at most one of them may be a duration.
+fn multiply(left: Value, right: Value) -> Result<Value, ErrorKind> {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left * right)),
+        (Value::Duration(seconds), Value::Number(factor))
+        | (Value::Number(factor), Value::Duration(seconds)) => {
+            Ok(Value::Duration(seconds * factor))
+        }
+        (Value::Duration(_), Value::Duration(_)) => Err(ErrorKind::MalformedExpression),
+    }
+}
+
+/// Divides two values; dividing two durations yields their ratio as a plain number.
+fn divide(left: Value, right: Value) -> Result<Value, ErrorKind> {
+    if right.seconds() == 0.0 {
+        return Err(ErrorKind::DivisionByZero);
+    }
+
+    match (left, right) {
+        (Value::Duration(seconds), Value::Number(divisor)) => {
+            Ok(Value::Duration(seconds / divisor))
+        }
+        (Value::Duration(left), Value::Duration(right))
+        | (Value::Number(left), Value::Number(right)) => Ok(Value::Number(left / right)),
+        (Value::Number(_), Value::Duration(_)) => Err(ErrorKind::MalformedExpression),
+    }
+}
+
+/// Parses a single quantity consisting of any number of NUMBER UNIT pairs, a clock-style time
+/// or an ISO 8601 duration. A lone NUMBER is returned as a plain number.
+fn parse_quantity(quantity: &str) -> Result<Value, ParsingError> {
+    if quantity.starts_with('P') {
+        return parse_iso8601(quantity).map(Value::Duration);
+    }
+
+    if quantity.contains(':') {
+        return parse_clock(quantity).map(Value::Duration);
+    }
+
+    // `f64` reads these as numbers, but split into digits and a unit they would pass for an
+    // empty number followed by an unknown unit.
+    if ["inf", "infinity", "nan"]
+        .iter()
+        .any(|word| quantity.eq_ignore_ascii_case(word))
+    {
+        return Err(ParsingError::at(ErrorKind::NotFinite, 0, quantity.len()));
+    }
+
+    if !quantity.contains(char::is_alphabetic) {
+        return parse_pair(quantity, "s")
+            .map(Value::Number)
+            .map_err(ParsingError::from);
+    }
+
+    let mut seconds = 0.0;
+    let mut start = 0;
+
+    while start < quantity.len() {
+        let rest = &quantity[start..];
+        let unit_start = start + rest.find(char::is_alphabetic).unwrap_or(rest.len());
+
+        let rest = &quantity[unit_start..];
+        let unit_end = unit_start
+            + rest
+                .find(|c: char| !c.is_alphabetic())
+                .unwrap_or(rest.len());
+
+        let value = &quantity[start..unit_start];
+        let unit = &quantity[unit_start..unit_end];
+
+        seconds += parse_pair(value, unit).map_err(|kind| match kind {
+            ErrorKind::InvalidUnit => {
+                let error = ParsingError::at(kind, unit_start, unit_end);
+                match suggest_unit(unit) {
+                    Some(suggestion) => error.suggest(format!(
+                        "{}{suggestion}{}",
+                        &quantity[..unit_start],
+                        &quantity[unit_end..]
+                    )),
+                    None => error,
+                }
+            }
+            _ => ParsingError::at(kind, start, unit_start.max(start + 1).min(quantity.len())),
+        })?;
+
+        start = unit_end;
+    }
+
+    Ok(Value::Duration(seconds))
+}
+
+/// Parses a clock-style argument such as `05:00`, `1:30:00` or `2:03:00:00.5`.
+/// Only the seconds may be fractional, and every field but the leading one must stay
+/// within its clock range.
+fn parse_clock(argument: &str) -> Result<f64, ParsingError> {
+    // Seconds per field and the range it has to stay within, from the right.
+    const FIELDS: [(f64, f64); 4] = [
+        (1.0, 60.0),
+        (60.0, 60.0),
+        (60.0 * 60.0, 24.0),
+        (60.0 * 60.0 * 24.0, f64::INFINITY),
+    ];
+
+    let fields: Vec<(usize, &str)> = argument
+        .split(':')
+        .scan(0, |start, field| {
+            let position = *start;
+            *start += field.len() + 1;
+            Some((position, field))
+        })
+        .collect();
+    if !(2..=FIELDS.len()).contains(&fields.len()) {
+        return Err(ErrorKind::MalformedClock.into());
+    }
+
+    let mut seconds = 0.0;
+
+    for (index, &(start, field)) in fields.iter().rev().enumerate() {
+        let end = start + field.len();
+        let is_number = field
+            .bytes()
+            .all(|byte| byte.is_ascii_digit() || (index == 0 && byte == b'.'));
+
+        let number = match field.parse::<f64>() {
+            Ok(number) if is_number && number.is_finite() => number,
+            Ok(_) if is_number => return Err(ParsingError::at(ErrorKind::NotFinite, start, end)),
+            _ => return Err(ParsingError::at(ErrorKind::InvalidNumber, start, end)),
+        };
+
+        let (scale, limit) = FIELDS[index];
+        if index + 1 < fields.len() && number >= limit {
+            return Err(ParsingError::at(ErrorKind::MalformedClock, start, end));
+        }
+
+        seconds += number * scale;
+    }
+
+    Ok(seconds)
+}
+
+/// Parses an ISO 8601 duration in the PnYnMnWnDTnHnMnS format, such as `PT1H30M` or `P1DT2H`.
+/// Years and months have no fixed length, so a year is taken as 365 days and a month as 30 days.
+/// Any component may carry a fraction, written with either a dot or a comma.
+fn parse_iso8601(argument: &str) -> Result<f64, ParsingError> {
+    const DATE: [(char, f64); 4] = [
+        ('Y', 60.0 * 60.0 * 24.0 * 365.0),
+        ('M', 60.0 * 60.0 * 24.0 * 30.0),
+        ('W', 60.0 * 60.0 * 24.0 * 7.0),
+        ('D', 60.0 * 60.0 * 24.0),
+    ];
+    const TIME: [(char, f64); 3] = [('H', 60.0 * 60.0), ('M', 60.0), ('S', 1.0)];
+
+    let (date, time) = match argument.find('T') {
+        Some(index) if index + 1 == argument.len() => {
+            return Err(ParsingError::at(
+                ErrorKind::MalformedIso8601,
+                index,
+                index + 1,
+            ))
+        }
+        Some(index) => ((1, index), (index + 1, argument.len())),
+        None => ((1, argument.len()), (argument.len(), argument.len())),
+    };
+
+    let mut seconds = 0.0;
+    let mut components = 0;
+
+    for ((mut start, end), designators) in [(date, &DATE[..]), (time, &TIME[..])] {
+        let mut designators = designators.iter();
+
+        while start < end {
+            let rest = &argument[start..end];
+            let value_end = start
+                + rest
+                    .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+                    .ok_or(ParsingError::at(ErrorKind::MalformedIso8601, end, end))?;
+
+            let designator = argument[value_end..].chars().next().unwrap();
+            let designator_end = value_end + designator.len_utf8();
+
+            // Designators have to appear in order, so the search resumes after the last match.
+            let scale = if let Some((_, scale)) = designators.find(|(d, _)| *d == designator) {
+                scale
+            } else {
+                return Err(ParsingError::at(
+                    ErrorKind::MalformedIso8601,
+                    value_end,
+                    designator_end,
+                ));
+            };
+
+            let value = &argument[start..value_end];
+            let number = if let Ok(number) = value.replace(',', ".").parse::<f64>() {
+                number
+            } else {
+                return Err(ParsingError::at(
+                    ErrorKind::InvalidNumber,
+                    start,
+                    designator_end,
+                ));
+            };
+
+            seconds += number * scale;
+            components += 1;
+            start = designator_end;
+        }
+    }
+
+    if components == 0 {
+        return Err(ErrorKind::MalformedIso8601.into());
+    }
+
+    Ok(seconds)
+}
+
+/// Units and their aliases with their length in seconds. Units are matched ignoring case.
+pub const UNITS: [(&str, f64); 35] = [
+    ("ns", 1e-9),
+    ("nanosecond", 1e-9),
+    ("nanoseconds", 1e-9),
+    ("us", 1e-6),
+    ("\u{b5}s", 1e-6),
+    ("\u{3bc}s", 1e-6),
+    ("microsecond", 1e-6),
+    ("microseconds", 1e-6),
+    ("ms", 1e-3),
+    ("msec", 1e-3),
+    ("millisecond", 1e-3),
+    ("milliseconds", 1e-3),
+    ("s", 1.0),
+    ("sec", 1.0),
+    ("secs", 1.0),
+    ("second", 1.0),
+    ("seconds", 1.0),
+    ("m", 60.0),
+    ("min", 60.0),
+    ("mins", 60.0),
+    ("minute", 60.0),
+    ("minutes", 60.0),
+    ("h", 60.0 * 60.0),
+    ("hr", 60.0 * 60.0),
+    ("hrs", 60.0 * 60.0),
+    ("hour", 60.0 * 60.0),
+    ("hours", 60.0 * 60.0),
+    ("d", 60.0 * 60.0 * 24.0),
+    ("day", 60.0 * 60.0 * 24.0),
+    ("days", 60.0 * 60.0 * 24.0),
+    ("w", 60.0 * 60.0 * 24.0 * 7.0),
+    ("week", 60.0 * 60.0 * 24.0 * 7.0),
+    ("weeks", 60.0 * 60.0 * 24.0 * 7.0),
+    ("wk", 60.0 * 60.0 * 24.0 * 7.0),
+    ("wks", 60.0 * 60.0 * 24.0 * 7.0),
+];
+
+/// Returns the length of a unit in seconds, accepting aliases such as `sec`, `mins` or
+/// `hours` in any case.
+pub fn unit_seconds(unit: &str) -> Option<f64> {
+    let unit = unit.to_lowercase();
+
+    UNITS
+        .iter()
+        .find(|(name, _)| *name == unit)
+        .map(|&(_, seconds)| seconds)
+}
+
+/// Converts a single NUMBER UNIT pair into seconds.
+fn parse_pair(value: &str, unit: &str) -> Result<f64, ErrorKind> {
+    let number = if let Ok(number) = value.parse::<f64>() {
+        number
+    } else {
+        return Err(ErrorKind::InvalidNumber);
+    };
+
+    if !number.is_finite() {
+        return Err(ErrorKind::NotFinite);
+    }
+
+    match unit_seconds(unit) {
+        Some(seconds) => Ok(number * seconds),
+        None => Err(ErrorKind::InvalidUnit),
+    }
+}
+
+/// Suggests the known unit closest to a misspelled one, such as `min` for `minn` or `hours`
+/// for `huors`.
+fn suggest_unit(unit: &str) -> Option<&'static str> {
+    let unit = unit.to_lowercase();
+
+    // Any single letter is one edit away from every short unit, so those get no suggestion.
+    let length = unit.chars().count();
+    if length < 2 {
+        return None;
+    }
+
+    let limit = (length / 3).max(1);
+
+    UNITS
+        .iter()
+        .map(|&(name, _)| name)
+        .filter(|name| edit_distance(name, &unit) <= limit)
+        // Prefer units the misspelling starts with, as in `m` for `mn`, then shorter ones.
+        .min_by_key(|name| {
+            (
+                edit_distance(name, &unit),
+                !unit.starts_with(name),
+                name.len(),
+            )
+        })
+}
+
+/// Returns the edit distance between two strings, counting insertions, deletions,
+/// substitutions and transpositions of adjacent characters as one edit each.
+pub fn edit_distance(left: &str, right: &str) -> usize {
+    let left: Vec<char> = left.chars().collect();
+    let right: Vec<char> = right.chars().collect();
+
+    let mut distances = vec![vec![0; right.len() + 1]; left.len() + 1];
+    distances[0] = (0..=right.len()).collect();
+    for (i, row) in distances.iter_mut().enumerate() {
+        row[0] = i;
+    }
+
+    for i in 1..=left.len() {
+        for j in 1..=right.len() {
+            let cost = usize::from(left[i - 1] != right[j - 1]);
+            let mut distance = (distances[i - 1][j] + 1)
+                .min(distances[i][j - 1] + 1)
+                .min(distances[i - 1][j - 1] + cost);
+
+            if i > 1 && j > 1 && left[i - 1] == right[j - 2] && left[i - 2] == right[j - 1] {
+                distance = distance.min(distances[i - 2][j - 2] + 1);
+            }
+
+            distances[i][j] = distance;
+        }
+    }
+
+    distances[left.len()][right.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(arguments: &[&str]) -> Result<Duration, ParsingError> {
+        parse_duration(
+            arguments
+                .iter()
+                .map(|argument| argument.to_string())
+                .collect(),
+        )
+    }
+
+    fn seconds(arguments: &[&str]) -> f64 {
+        parse(arguments).unwrap().as_secs_f64()
+    }
+
+    /// Returns the kind of the error and the argument and byte range it points at.
+    fn error(arguments: &[&str]) -> (ErrorKind, usize, usize, usize) {
+        let error = parse(arguments).unwrap_err();
+        let location = error.location.expect("error without a location");
+        (error.kind, location.argument, location.start, location.end)
+    }
+
+    #[test]
+    fn evaluates_arithmetic() {
+        assert_eq!(seconds(&["2h-15m"]), 6_300.0);
+        assert_eq!(seconds(&["3*25m"]), 4_500.0);
+        assert_eq!(seconds(&["(25m+5m)*4"]), 7_200.0);
+        assert_eq!(seconds(&["1h/3"]), 1_200.0);
+        assert_eq!(seconds(&["25m*3"]), 4_500.0);
+        assert_eq!(seconds(&["1h - 2 * 15m"]), 1_800.0);
+        assert_eq!(seconds(&["-15m+1h"]), 2_700.0);
+        assert_eq!(seconds(&["((1h))"]), 3_600.0);
+    }
+
+    #[test]
+    fn adds_quantities_written_next_to_each_other() {
+        assert_eq!(seconds(&["1h", "30m"]), 5_400.0);
+        assert_eq!(seconds(&["1h 30m"]), 5_400.0);
+        assert_eq!(seconds(&["1h30m15s"]), 5_415.0);
+        assert_eq!(seconds(&["1h", "30m", "15s"]), 5_415.0);
+        assert_eq!(seconds(&["2h", "-15m"]), 6_300.0);
+        assert_eq!(seconds(&["2h", "-", "15m"]), 6_300.0);
+        assert_eq!(seconds(&["(25m+5m)", "*4"]), 7_200.0);
+    }
+
+    #[test]
+    fn accepts_plain_numbers_clocks_iso_8601_and_aliases() {
+        assert_eq!(seconds(&["90"]), 90.0);
+        assert_eq!(seconds(&["1.5m"]), 90.0);
+        assert_eq!(seconds(&["1:30:00"]), 5_400.0);
+        assert_eq!(seconds(&["PT1H30M"]), 5_400.0);
+        assert_eq!(seconds(&["2Hours"]), 7_200.0);
+        assert_eq!(seconds(&["1wk"]), 604_800.0);
+        assert_eq!(seconds(&["500ms"]), 0.5);
+    }
+
+    #[test]
+    fn points_negative_results_at_the_whole_expression() {
+        assert_eq!(error(&["1h-2h"]), (ErrorKind::Negative, 0, 0, 5));
+        assert_eq!(error(&["1h - 2h"]), (ErrorKind::Negative, 0, 0, 7));
+        assert_eq!(error(&["-5m"]), (ErrorKind::Negative, 0, 0, 3));
+
+        // Across arguments, only the first token is underlined.
+        assert_eq!(error(&["1h", "-", "2h"]), (ErrorKind::Negative, 0, 0, 2));
+    }
+
+    #[test]
+    fn points_division_by_zero_at_the_divisor() {
+        assert_eq!(error(&["1h/0"]), (ErrorKind::DivisionByZero, 0, 3, 4));
+        assert_eq!(error(&["1h/(5-5)"]), (ErrorKind::DivisionByZero, 0, 3, 8));
+        assert_eq!(error(&["1h", "/0s"]), (ErrorKind::DivisionByZero, 1, 1, 3));
+    }
+
+    #[test]
+    fn points_malformed_expressions_at_the_offending_token() {
+        assert_eq!(error(&["1h)"]), (ErrorKind::MalformedExpression, 0, 2, 3));
+        assert_eq!(error(&["1h**2"]), (ErrorKind::MalformedExpression, 0, 3, 4));
+        assert_eq!(error(&["1h+*2"]), (ErrorKind::MalformedExpression, 0, 3, 4));
+        assert_eq!(error(&["()"]), (ErrorKind::MalformedExpression, 0, 1, 2));
+
+        // Durations cannot be multiplied together or divide a number.
+        assert_eq!(error(&["1h*1h"]), (ErrorKind::MalformedExpression, 0, 2, 3));
+        assert_eq!(error(&["2/1h"]), (ErrorKind::MalformedExpression, 0, 1, 2));
+    }
+
+    #[test]
+    fn points_unexpected_ends_just_behind_the_input() {
+        assert_eq!(error(&["1h*"]), (ErrorKind::UnexpectedEnd, 0, 3, 3));
+        assert_eq!(error(&["1h", "+"]), (ErrorKind::UnexpectedEnd, 1, 1, 1));
+
+        let unclosed = parse(&["(1h"]).unwrap_err();
+        assert_eq!(unclosed.kind, ErrorKind::UnexpectedEnd);
+        assert_eq!(unclosed.suggestion.as_deref(), Some("(1h)"));
+    }
+
+    #[test]
+    fn points_bad_quantities_at_their_argument() {
+        let unit = parse(&["1h", "5mni"]).unwrap_err();
+        assert_eq!(unit.kind, ErrorKind::InvalidUnit);
+        assert_eq!(unit.suggestion.as_deref(), Some("5min"));
+        let location = unit.location.unwrap();
+        assert_eq!((location.argument, location.text()), (1, "mni"));
+
+        assert_eq!(error(&[""]), (ErrorKind::InvalidNumber, 0, 0, 0));
+    }
+
+    #[test]
+    fn rejects_numbers_that_are_not_finite() {
+        assert_eq!(error(&["inf"]), (ErrorKind::NotFinite, 0, 0, 3));
+        assert_eq!(error(&["1h", "Infinity"]), (ErrorKind::NotFinite, 1, 0, 8));
+        assert_eq!(error(&["NaN"]), (ErrorKind::NotFinite, 0, 0, 3));
+    }
+
+    #[test]
+    fn rejects_durations_longer_than_the_maximum() {
+        assert_eq!(parse(&["101*365d"]).unwrap_err().kind, ErrorKind::Overflow);
+        assert!(parse(&["99*365d"]).is_ok());
+    }
+}
@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicBool, AtomicI32, Ordering};
+use std::time::Duration;
+
+/// Set by the SIGWINCH handler whenever the terminal is resized.
+static RESIZED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_resize(_signal: libc::c_int) {
+    RESIZED.store(true, Ordering::Relaxed);
+}
+
+/// Installs a SIGWINCH handler so that `take_resized` reports terminal resizes.
+pub fn watch_resize() {
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGWINCH, on_resize as *const () as libc::sighandler_t);
+    }
+}
+
+/// Returns whether the terminal was resized since the last call.
+pub fn take_resized() -> bool {
+    RESIZED.swap(false, Ordering::Relaxed)
+}
+
+/// The last terminating signal received, or 0 if there was none.
+static SIGNAL: AtomicI32 = AtomicI32::new(0);
+
+extern "C" fn on_signal(signal: libc::c_int) {
+    SIGNAL.store(signal, Ordering::Relaxed);
+}
+
+/// Installs handlers for the given signals so that, rather than ending the program on the
+/// spot, they are reported by `take_signal` and the terminal can be restored first.
+pub fn watch_signals(signals: &[libc::c_int]) {
+    for &signal in signals {
+        // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+        unsafe {
+            libc::signal(signal, on_signal as *const () as libc::sighandler_t);
+        }
+    }
+}
+
+/// Returns the signal received since the last call, if any.
+pub fn take_signal() -> Option<libc::c_int> {
+    match SIGNAL.swap(0, Ordering::Relaxed) {
+        0 => None,
+        signal => Some(signal),
+    }
+}
+
+/// Set by the SIGUSR1 handler whenever a lap is requested.
+static LAP: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_lap(_signal: libc::c_int) {
+    LAP.store(true, Ordering::Relaxed);
+}
+
+/// Installs a SIGUSR1 handler so that `take_lap` reports laps requested from outside, such
+/// as with `kill -USR1`.
+pub fn watch_lap() {
+    // SAFETY: the handler only stores to an atomic, which is async-signal-safe.
+    unsafe {
+        libc::signal(libc::SIGUSR1, on_lap as *const () as libc::sighandler_t);
+    }
+}
+
+/// Returns whether a lap was requested since the last call.
+pub fn take_lap() -> bool {
+    LAP.swap(false, Ordering::Relaxed)
+}
+
+/// Returns whether stdout is a terminal.
+pub fn is_terminal() -> bool {
+    // SAFETY: isatty only inspects the file descriptor.
+    unsafe { libc::isatty(libc::STDOUT_FILENO) == 1 }
+}
+
+/// Returns the size of the terminal as (columns, rows), or 80x24 if stdout is not a terminal.
+pub fn size() -> (usize, usize) {
+    // SAFETY: `winsize` is plain data and TIOCGWINSZ only writes into it.
+    let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+    let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+
+    if result == -1 || size.ws_col == 0 || size.ws_row == 0 {
+        return (80, 24);
+    }
+
+    (size.ws_col as usize, size.ws_row as usize)
+}
+
+/// Returns how many columns text takes up in a terminal, counting East Asian wide characters,
+/// such as kanji and kana, as two.
+pub fn width(text: &str) -> usize {
+    text.chars()
+        .map(|c| match c as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6
+            | 0x20000..=0x3FFFD => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+/// The settings of the terminal on stdin from before raw mode was enabled.
+pub struct RawMode {
+    original: libc::termios,
+}
+
+impl RawMode {
+    /// Turns off line buffering and echo on stdin so that single key presses can be read,
+    /// leaving signal keys such as Ctrl-C working. Returns `None` if stdin is not a terminal
+    /// or belongs to another process group, as when running in the background.
+    pub fn enable() -> Option<RawMode> {
+        // SAFETY: these calls only inspect and update the terminal settings of stdin, and
+        // `termios` is plain data.
+        unsafe {
+            if libc::isatty(libc::STDIN_FILENO) != 1
+                || libc::tcgetpgrp(libc::STDIN_FILENO) != libc::getpgrp()
+            {
+                return None;
+            }
+
+            let mut original: libc::termios = std::mem::zeroed();
+            if libc::tcgetattr(libc::STDIN_FILENO, &mut original) != 0 {
+                return None;
+            }
+
+            let mut raw = original;
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+            raw.c_cc[libc::VMIN] = 1;
+            raw.c_cc[libc::VTIME] = 0;
+
+            if libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) != 0 {
+                return None;
+            }
+
+            Some(RawMode { original })
+        }
+    }
+
+    /// Puts the terminal settings back the way they were.
+    pub fn restore(&self) {
+        // SAFETY: see `enable`.
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &self.original);
+        }
+    }
+}
+
+/// Waits up to `timeout` for a key press on stdin and returns it.
+pub fn read_key(timeout: Duration) -> Option<u8> {
+    let mut poll = libc::pollfd {
+        fd: libc::STDIN_FILENO,
+        events: libc::POLLIN,
+        revents: 0,
+    };
+
+    // SAFETY: poll and read only write into the structures we hand them.
+    unsafe {
+        if libc::poll(&mut poll, 1, timeout.as_millis() as libc::c_int) != 1 {
+            return None;
+        }
+
+        let mut key = 0u8;
+        let read = libc::read(libc::STDIN_FILENO, (&mut key as *mut u8).cast(), 1);
+        (read == 1).then_some(key)
+    }
+}
@@ -0,0 +1,117 @@
+use std::time::{Duration, Instant, SystemTime};
+
+/// A countdown that can be paused, lengthened, shortened and restarted while it runs, or a
+/// stopwatch that runs until it is stopped.
+#[derive(Debug)]
+pub struct Timer {
+    /// How long the timer runs, as given at the start, or `None` for a stopwatch.
+    original: Option<Duration>,
+    /// How long the timer runs, after adding and subtracting time.
+    duration: Option<Duration>,
+    /// When the current run started, on the monotonic and on the wall clock.
+    started: Instant,
+    started_at: SystemTime,
+    /// When the timer was paused, if it is paused.
+    paused_since: Option<Instant>,
+    /// How long the timer was paused for, not counting the current pause.
+    paused: Duration,
+    /// The elapsed time at each lap.
+    laps: Vec<Duration>,
+}
+
+impl Timer {
+    /// Starts a timer that runs for `duration`.
+    pub fn start(duration: Duration) -> Timer {
+        Timer::new(Some(duration))
+    }
+
+    /// Starts a stopwatch, which has no end.
+    pub fn stopwatch() -> Timer {
+        Timer::new(None)
+    }
+
+    fn new(duration: Option<Duration>) -> Timer {
+        Timer {
+            original: duration,
+            duration,
+            started: Instant::now(),
+            started_at: SystemTime::now(),
+            paused_since: None,
+            paused: Duration::ZERO,
+            laps: Vec::new(),
+        }
+    }
+
+    /// Returns how long the timer runs in total, or `None` for a stopwatch.
+    pub fn duration(&self) -> Option<Duration> {
+        self.duration
+    }
+
+    /// Returns how long the timer has been paused for, including the current pause.
+    pub fn paused(&self) -> Duration {
+        self.paused_at(Instant::now())
+    }
+
+    fn paused_at(&self, now: Instant) -> Duration {
+        match self.paused_since {
+            Some(since) => self.paused + now.duration_since(since),
+            None => self.paused,
+        }
+    }
+
+    /// Returns how long the timer has run, not counting pauses.
+    pub fn elapsed(&self) -> Duration {
+        // Measure both at the same instant, so the elapsed time stands still while paused.
+        let now = Instant::now();
+        now.duration_since(self.started)
+            .saturating_sub(self.paused_at(now))
+    }
+
+    /// Returns the wall-clock time at which the timer ends, if it is not paused any further,
+    /// or `None` for a stopwatch.
+    pub fn ends_at(&self) -> Option<SystemTime> {
+        let duration = self.duration?;
+        Some(self.started_at + self.paused() + duration)
+    }
+
+    /// Pauses a running timer, or resumes a paused one.
+    pub fn toggle_pause(&mut self) {
+        match self.paused_since.take() {
+            Some(since) => self.paused += since.elapsed(),
+            None => self.paused_since = Some(Instant::now()),
+        }
+    }
+
+    /// Lengthens the timer. A stopwatch stays without an end.
+    pub fn add(&mut self, duration: Duration) {
+        self.duration = self.duration.map(|total| total.saturating_add(duration));
+    }
+
+    /// Shortens the timer, at most down to the time already elapsed. A stopwatch stays
+    /// without an end.
+    pub fn subtract(&mut self, duration: Duration) {
+        let elapsed = self.elapsed();
+        self.duration = self
+            .duration
+            .map(|total| total.saturating_sub(duration).max(elapsed));
+    }
+
+    /// Starts the timer over with its original duration, running and without laps.
+    pub fn restart(&mut self) {
+        *self = Timer::new(self.original);
+    }
+
+    /// Returns the elapsed time at each lap.
+    pub fn laps(&self) -> &[Duration] {
+        &self.laps
+    }
+
+    /// Records a lap at the elapsed time, and returns its number and length.
+    pub fn lap(&mut self) -> (usize, Duration) {
+        let elapsed = self.elapsed();
+        let previous = self.laps.last().copied().unwrap_or_default();
+
+        self.laps.push(elapsed);
+        (self.laps.len(), elapsed.saturating_sub(previous))
+    }
+}
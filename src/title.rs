@@ -0,0 +1,54 @@
+use std::env;
+use std::process::Command;
+
+/// The terminal window title, set while the timer runs and restored afterwards.
+///
+/// Terminals keep a stack of titles (xterm's `CSI 22 t` and `CSI 23 t`), so the previous title
+/// is pushed before the first update and popped at the end. Inside tmux the window name is set
+/// as well, and the previous one is looked up so that it can be put back.
+#[derive(Debug)]
+pub struct Title {
+    /// The name of the tmux window, if running inside tmux.
+    tmux_name: Option<String>,
+}
+
+impl Title {
+    /// Saves the current title and returns the escape sequence that does so.
+    pub fn save() -> (Title, String) {
+        let tmux_name = env::var_os("TMUX").and_then(|_| {
+            let output = Command::new("tmux")
+                .args(["display-message", "-p", "#W"])
+                .output()
+                .ok()?;
+
+            let name = String::from_utf8(output.stdout).ok()?;
+            output.status.success().then(|| name.trim_end().to_string())
+        });
+
+        (Title { tmux_name }, String::from("\x1b[22;0t"))
+    }
+
+    /// Returns the escape sequences that set the title to `text`, via OSC 0 and, inside tmux,
+    /// the window-name escape.
+    pub fn set(&self, text: &str) -> String {
+        // Control characters would end the escape sequence early.
+        let text: String = text.chars().filter(|c| !c.is_control()).collect();
+
+        let mut escape = format!("\x1b]0;{text}\x07");
+        if self.tmux_name.is_some() {
+            escape.push_str(&format!("\x1bk{text}\x1b\\"));
+        }
+
+        escape
+    }
+
+    /// Returns the escape sequences that restore the title saved by `save`.
+    pub fn restore(&self) -> String {
+        let mut escape = String::from("\x1b[23;0t");
+        if let Some(name) = &self.tmux_name {
+            escape.push_str(&format!("\x1bk{name}\x1b\\"));
+        }
+
+        escape
+    }
+}
@@ -0,0 +1,199 @@
+use std::time::Duration;
+
+use crate::bar;
+use crate::big;
+use crate::parse::parse_duration;
+use crate::terminal;
+
+/// Switches to the alternate screen and hides the cursor.
+pub const ENTER: &str = "\x1b[?1049h\x1b[?25l";
+
+/// Shows the cursor and switches back to the normal screen.
+pub const LEAVE: &str = "\x1b[?25h\x1b[?1049l";
+
+/// One of the durations that make up the timer, run one after the other.
+#[derive(Debug, Clone)]
+pub struct Segment {
+    /// The argument the segment was given as.
+    pub label: String,
+    pub duration: Duration,
+}
+
+/// Splits the timer into one segment per argument, if each argument is a duration of its own
+/// and together they add up to `total`. Otherwise the whole timer is a single segment.
+pub fn segments(arguments: &[String], total: Duration) -> Vec<Segment> {
+    let whole = vec![Segment {
+        label: arguments.join(" "),
+        duration: total,
+    }];
+
+    if arguments.len() < 2 {
+        return whole;
+    }
+
+    let segments: Option<Vec<Segment>> = arguments
+        .iter()
+        .map(|argument| {
+            let duration = parse_duration(vec![argument.clone()]).ok()?;
+            Some(Segment {
+                label: argument.clone(),
+                duration,
+            })
+        })
+        .collect();
+
+    match segments {
+        Some(segments)
+            if segments
+                .iter()
+                .map(|segment| segment.duration)
+                .sum::<Duration>()
+                == total =>
+        {
+            segments
+        }
+        _ => whole,
+    }
+}
+
+/// Returns the index of the segment running after `elapsed`, and how long it has run.
+pub fn current(segments: &[Segment], elapsed: Duration) -> (usize, Duration) {
+    let mut start = Duration::ZERO;
+
+    for (index, segment) in segments.iter().enumerate() {
+        if elapsed < start + segment.duration || index + 1 == segments.len() {
+            return (index, elapsed.saturating_sub(start));
+        }
+        start += segment.duration;
+    }
+
+    (0, elapsed)
+}
+
+/// Everything shown on the full-screen display.
+pub struct Screen<'a> {
+    /// The times drawn in large digits.
+    pub clock: &'a [String],
+    /// The SGR escape sequence the clock is drawn with, if any.
+    pub color: Option<&'a str>,
+    /// How much of the timer has passed, from 0 to 1, if it ends at all.
+    pub progress: Option<f64>,
+    /// The segments with the text shown next to each, and the index of the running one.
+    pub segments: &'a [(String, String)],
+    pub current: usize,
+    /// Keys and what they do.
+    pub hints: &'a str,
+    pub status: &'a str,
+    pub unicode: bool,
+}
+
+impl Screen<'_> {
+    /// Returns the escape sequences that draw the screen in a terminal of the given size: the
+    /// clock, progress bar and segments centred above a line of key hints and a status line.
+    /// Every row is redrawn in place, so the screen does not need clearing first.
+    pub fn render(&self, (columns, rows): (usize, usize)) -> String {
+        let mut body: Vec<(String, bool)> = big::lines(self.clock, columns, self.unicode)
+            .into_iter()
+            .map(|line| (line, true))
+            .collect();
+
+        // The status and hint lines take the bottom two rows; drop what does not fit above.
+        let height = rows.saturating_sub(2);
+
+        let bar = self
+            .progress
+            .and_then(|fraction| bar::fit(fraction, 0, columns * 2 / 3, self.unicode));
+        if let Some(bar) = bar {
+            if body.len() + 2 <= height {
+                body.push((String::new(), false));
+                body.push((bar, false));
+            }
+        }
+
+        if self.segments.len() > 1 && body.len() + 1 + self.segments.len() <= height {
+            body.push((String::new(), false));
+            body.extend(self.segment_lines().into_iter().map(|line| (line, false)));
+        }
+
+        let (color, reset) = match self.color {
+            Some(color) => (color, "\x1b[0m"),
+            None => ("", ""),
+        };
+
+        let mut screen = String::new();
+        let top = height.saturating_sub(body.len()) / 2;
+
+        for row in 0..height {
+            let Some((line, is_clock)) = row.checked_sub(top).and_then(|index| body.get(index))
+            else {
+                screen.push_str(&format!("\x1b[{};1H\x1b[2K", row + 1));
+                continue;
+            };
+
+            let left = columns.saturating_sub(terminal::width(line)) / 2;
+            screen.push_str(&format!("\x1b[{};{}H\x1b[1K", row + 1, left + 1));
+
+            if *is_clock {
+                screen.push_str(&format!("{color}{line}{reset}\x1b[K"));
+            } else {
+                screen.push_str(&format!("{line}\x1b[K"));
+            }
+        }
+
+        if rows >= 2 {
+            let hints: String = self.hints.chars().take(columns).collect();
+            screen.push_str(&format!("\x1b[{};1H\x1b[2m{hints}\x1b[0m\x1b[K", rows - 1));
+        }
+
+        let status: String = self.status.chars().take(columns).collect();
+        let padding = columns - status.chars().count();
+        screen.push_str(&format!(
+            "\x1b[{rows};1H\x1b[7m{status}{}\x1b[0m",
+            " ".repeat(padding)
+        ));
+
+        screen
+    }
+
+    /// Returns the list of segments, marked as done, running or queued, with their texts
+    /// aligned in a column.
+    fn segment_lines(&self) -> Vec<String> {
+        let (done, running, queued) = if self.unicode {
+            ('✓', '▶', '·')
+        } else {
+            ('x', '>', '-')
+        };
+
+        let width = self
+            .segments
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or(0);
+
+        let lines: Vec<String> = self
+            .segments
+            .iter()
+            .enumerate()
+            .map(|(index, (label, text))| {
+                let marker = match index.cmp(&self.current) {
+                    std::cmp::Ordering::Less => done,
+                    std::cmp::Ordering::Equal => running,
+                    std::cmp::Ordering::Greater => queued,
+                };
+                format!("{marker} {label:width$}  {text}")
+            })
+            .collect();
+
+        // Pad the lines to the same width so that they stay aligned when centred.
+        let longest = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0);
+        lines
+            .into_iter()
+            .map(|line| format!("{line:longest$}"))
+            .collect()
+    }
+}
@@ -0,0 +1,811 @@
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+pub const SECONDS_PER_DAY: i64 = 60 * 60 * 24;
+
+/// The directory holding the system time zone database, unless overridden by `TZDIR`.
+const ZONEINFO_DIR: &str = "/usr/share/zoneinfo";
+
+/// A calendar date in the proleptic Gregorian calendar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Date {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+}
+
+/// An offset from UTC together with its abbreviation, such as `EDT`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalType {
+    pub offset: i64,
+    pub is_dst: bool,
+    pub abbreviation: String,
+}
+
+/// An IANA time zone loaded from a TZif file of the system time zone database.
+#[derive(Debug)]
+pub struct TimeZone {
+    pub name: String,
+    transitions: Vec<i64>,
+    type_indices: Vec<usize>,
+    types: Vec<LocalType>,
+    rule: Option<Rule>,
+}
+
+impl TimeZone {
+    /// Loads the named zone (e.g., `America/New_York`) from `TZDIR` or /usr/share/zoneinfo.
+    pub fn load(name: &str) -> Option<TimeZone> {
+        let is_valid = !name.is_empty()
+            && !name.starts_with('/')
+            && name
+                .split('/')
+                .all(|part| !part.is_empty() && part != "." && part != "..");
+        if !is_valid {
+            return None;
+        }
+
+        let directory = env::var_os("TZDIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from(ZONEINFO_DIR));
+        let data = fs::read(directory.join(name)).ok()?;
+
+        let mut zone = parse_tzif(&data)?;
+        zone.name = name.to_string();
+        Some(zone)
+    }
+
+    /// Returns the local time type in effect at the given Unix time.
+    pub fn local_type(&self, seconds: i64) -> LocalType {
+        let is_past_transitions = self.transitions.last().is_none_or(|&last| seconds >= last);
+        if let (true, Some(rule)) = (is_past_transitions, &self.rule) {
+            return rule.local_type(seconds);
+        }
+
+        match self
+            .transitions
+            .partition_point(|&transition| transition <= seconds)
+        {
+            0 => self.types[0].clone(),
+            index => self.types[self.type_indices[index - 1]].clone(),
+        }
+    }
+
+    /// Converts wall-clock seconds in this zone into Unix time.
+    /// A wall-clock time that occurs twice resolves to the earlier instant, and one skipped by a
+    /// forward transition is moved forward by the length of the gap.
+    pub fn to_unix_seconds(&self, local: i64) -> i64 {
+        let before = self.local_type(local - SECONDS_PER_DAY).offset;
+        let after = self.local_type(local + SECONDS_PER_DAY).offset;
+
+        let mut candidates: Vec<i64> = [before, after]
+            .into_iter()
+            .map(|offset| local - offset)
+            .filter(|&seconds| local - self.local_type(seconds).offset == seconds)
+            .collect();
+        candidates.sort();
+
+        candidates.first().copied().unwrap_or(local - before)
+    }
+}
+
+/// A POSIX TZ rule, as found in the footer of TZif files, describing times past the last
+/// transition (e.g., `EST5EDT,M3.2.0,M11.1.0`).
+#[derive(Debug)]
+struct Rule {
+    standard: LocalType,
+    daylight: Option<(LocalType, RuleDate, i64, RuleDate, i64)>,
+}
+
+/// The day of the year a daylight saving time transition happens on.
+#[derive(Debug, Clone, Copy)]
+enum RuleDate {
+    /// `Jn`: day 1 to 365, never counting February 29.
+    Julian(u32),
+    /// `n`: day 0 to 365, counting February 29 in leap years.
+    Ordinal(u32),
+    /// `Mm.w.d`: weekday `d` of week `w` (5 being the last) of month `m`.
+    Weekday(u32, u32, u32),
+}
+
+impl Rule {
+    fn local_type(&self, seconds: i64) -> LocalType {
+        let Some((daylight, start, start_time, end, end_time)) = &self.daylight else {
+            return self.standard.clone();
+        };
+
+        let year =
+            civil_from_days((seconds + self.standard.offset).div_euclid(SECONDS_PER_DAY)).year;
+
+        // Transitions are given in the local time in effect just before them.
+        let start = start.day(year) * SECONDS_PER_DAY + start_time - self.standard.offset;
+        let end = end.day(year) * SECONDS_PER_DAY + end_time - daylight.offset;
+
+        let is_dst = if start < end {
+            start <= seconds && seconds < end
+        } else {
+            !(end <= seconds && seconds < start)
+        };
+
+        if is_dst {
+            daylight.clone()
+        } else {
+            self.standard.clone()
+        }
+    }
+
+    /// Parses a POSIX TZ string.
+    fn parse(text: &str) -> Option<Rule> {
+        let mut cursor = Cursor { rest: text };
+
+        let standard_name = cursor.abbreviation()?;
+        let standard_offset = -cursor.offset()?;
+        let standard = LocalType {
+            offset: standard_offset,
+            is_dst: false,
+            abbreviation: standard_name,
+        };
+
+        if cursor.rest.is_empty() {
+            return Some(Rule {
+                standard,
+                daylight: None,
+            });
+        }
+
+        let daylight_name = cursor.abbreviation()?;
+        let daylight_offset = if cursor.rest.is_empty() || cursor.rest.starts_with(',') {
+            standard_offset + 60 * 60
+        } else {
+            -cursor.offset()?
+        };
+        let daylight = LocalType {
+            offset: daylight_offset,
+            is_dst: true,
+            abbreviation: daylight_name,
+        };
+
+        // Without explicit dates POSIX leaves the rule implementation defined; use the US one.
+        if cursor.rest.is_empty() {
+            cursor.rest = ",M3.2.0,M11.1.0";
+        }
+
+        let (start, start_time) = cursor.transition()?;
+        let (end, end_time) = cursor.transition()?;
+
+        if !cursor.rest.is_empty() {
+            return None;
+        }
+
+        Some(Rule {
+            standard,
+            daylight: Some((daylight, start, start_time, end, end_time)),
+        })
+    }
+}
+
+impl RuleDate {
+    /// Returns the days since 1970-01-01 this rule date falls on in the given year.
+    fn day(self, year: i64) -> i64 {
+        let january_first = days_from_civil(Date {
+            year,
+            month: 1,
+            day: 1,
+        });
+
+        match self {
+            RuleDate::Julian(day) => {
+                let skips_leap_day = day > 59 && days_in_month(year, 2) == 29;
+                january_first + day as i64 - 1 + skips_leap_day as i64
+            }
+            RuleDate::Ordinal(day) => january_first + day as i64,
+            RuleDate::Weekday(month, week, weekday) => {
+                let first = days_from_civil(Date {
+                    year,
+                    month,
+                    day: 1,
+                });
+                // 1970-01-01 was a Thursday.
+                let first_weekday = (first + 4).rem_euclid(7) as u32;
+                let mut day = 1 + (weekday + 7 - first_weekday) % 7 + (week - 1) * 7;
+                while day > days_in_month(year, month) {
+                    day -= 7;
+                }
+
+                first + day as i64 - 1
+            }
+        }
+    }
+}
+
+/// A small scanner over a POSIX TZ string.
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl Cursor<'_> {
+    /// Reads a zone abbreviation, either alphabetic or quoted in angle brackets.
+    fn abbreviation(&mut self) -> Option<String> {
+        let (name, rest) = if let Some(quoted) = self.rest.strip_prefix('<') {
+            let (name, rest) = quoted.split_once('>')?;
+            (name, rest)
+        } else {
+            let index = self
+                .rest
+                .find(|c: char| !c.is_ascii_alphabetic())
+                .unwrap_or(self.rest.len());
+            self.rest.split_at(index)
+        };
+
+        if name.len() < 3 {
+            return None;
+        }
+
+        self.rest = rest;
+        Some(name.to_string())
+    }
+
+    /// Reads a signed [+-]hh[:mm[:ss]] value in seconds.
+    fn offset(&mut self) -> Option<i64> {
+        let sign = match self.rest.as_bytes().first()? {
+            b'-' => -1,
+            b'+' => 1,
+            _ => return self.hms(),
+        };
+
+        self.rest = &self.rest[1..];
+        Some(sign * self.hms()?)
+    }
+
+    /// Reads an unsigned hh[:mm[:ss]] value in seconds.
+    fn hms(&mut self) -> Option<i64> {
+        let mut seconds = 0;
+
+        for (index, scale) in [60 * 60, 60, 1].into_iter().enumerate() {
+            if index > 0 {
+                match self.rest.strip_prefix(':') {
+                    Some(rest) => self.rest = rest,
+                    None => break,
+                }
+            }
+
+            seconds += self.number()? as i64 * scale;
+        }
+
+        Some(seconds)
+    }
+
+    /// Reads an unsigned decimal number.
+    fn number(&mut self) -> Option<u32> {
+        let index = self
+            .rest
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(self.rest.len());
+        let (digits, rest) = self.rest.split_at(index);
+
+        self.rest = rest;
+        digits.parse().ok()
+    }
+
+    /// Reads a `,date[/time]` transition, the time defaulting to 02:00.
+    fn transition(&mut self) -> Option<(RuleDate, i64)> {
+        self.rest = self.rest.strip_prefix(',')?;
+
+        let date = if let Some(rest) = self.rest.strip_prefix('J') {
+            self.rest = rest;
+            RuleDate::Julian(self.number().filter(|day| (1..=365).contains(day))?)
+        } else if let Some(rest) = self.rest.strip_prefix('M') {
+            self.rest = rest;
+            let month = self.number().filter(|month| (1..=12).contains(month))?;
+            self.rest = self.rest.strip_prefix('.')?;
+            let week = self.number().filter(|week| (1..=5).contains(week))?;
+            self.rest = self.rest.strip_prefix('.')?;
+            let weekday = self.number().filter(|weekday| *weekday <= 6)?;
+            RuleDate::Weekday(month, week, weekday)
+        } else {
+            RuleDate::Ordinal(self.number().filter(|day| *day <= 365)?)
+        };
+
+        let time = match self.rest.strip_prefix('/') {
+            Some(rest) => {
+                self.rest = rest;
+                self.offset()?
+            }
+            None => 2 * 60 * 60,
+        };
+
+        Some((date, time))
+    }
+}
+
+/// Parses the contents of a TZif file as described in RFC 8536.
+fn parse_tzif(data: &[u8]) -> Option<TimeZone> {
+    let header = parse_header(data)?;
+
+    // Version 1 files only carry 32-bit data; later versions repeat it with 64-bit times and
+    // append a POSIX TZ string footer.
+    if header.version == 0 {
+        return parse_block(data, &header, 4);
+    }
+
+    let rest = data.get(header.block_length(4)..)?;
+    let header = parse_header(rest)?;
+    let mut zone = parse_block(rest, &header, 8)?;
+
+    let footer = rest.get(header.block_length(8)..)?;
+    let footer = std::str::from_utf8(footer).ok()?;
+    let footer = footer.strip_prefix('\n')?.split('\n').next()?;
+    if !footer.is_empty() {
+        zone.rule = Rule::parse(footer);
+    }
+
+    Some(zone)
+}
+
+const HEADER_LENGTH: usize = 44;
+
+/// The counts from a TZif header.
+struct Header {
+    version: u8,
+    is_ut_count: usize,
+    is_std_count: usize,
+    leap_count: usize,
+    time_count: usize,
+    type_count: usize,
+    char_count: usize,
+}
+
+impl Header {
+    /// Returns the length of the header and its data block for the given time size.
+    fn block_length(&self, time_size: usize) -> usize {
+        HEADER_LENGTH
+            + self.time_count * time_size
+            + self.time_count
+            + self.type_count * 6
+            + self.char_count
+            + self.leap_count * (time_size + 4)
+            + self.is_std_count
+            + self.is_ut_count
+    }
+}
+
+fn parse_header(data: &[u8]) -> Option<Header> {
+    if data.get(..4)? != b"TZif" {
+        return None;
+    }
+
+    let count = |index: usize| -> Option<usize> {
+        let bytes = data.get(20 + index * 4..24 + index * 4)?;
+        Some(u32::from_be_bytes(bytes.try_into().ok()?) as usize)
+    };
+
+    // Version 1 files have a NUL byte here, and later versions an ASCII digit.
+    let version = match *data.get(4)? {
+        0 => 0,
+        version @ b'2'..=b'4' => version - b'0',
+        _ => return None,
+    };
+
+    let header = Header {
+        version,
+        is_ut_count: count(0)?,
+        is_std_count: count(1)?,
+        leap_count: count(2)?,
+        time_count: count(3)?,
+        type_count: count(4)?,
+        char_count: count(5)?,
+    };
+
+    if header.type_count == 0 {
+        return None;
+    }
+
+    Some(header)
+}
+
+/// Parses the transitions and local time types of a data block.
+fn parse_block(data: &[u8], header: &Header, time_size: usize) -> Option<TimeZone> {
+    let mut rest = data.get(HEADER_LENGTH..header.block_length(time_size))?;
+    let mut take = |length: usize| -> &[u8] {
+        let (head, tail) = rest.split_at(length);
+        rest = tail;
+        head
+    };
+
+    let transitions = take(header.time_count * time_size)
+        .chunks(time_size)
+        .map(|chunk| match time_size {
+            4 => i32::from_be_bytes(chunk.try_into().unwrap()) as i64,
+            _ => i64::from_be_bytes(chunk.try_into().unwrap()),
+        })
+        .collect();
+
+    let type_indices: Vec<usize> = take(header.time_count)
+        .iter()
+        .map(|&index| index as usize)
+        .collect();
+
+    let records: Vec<&[u8]> = take(header.type_count * 6).chunks(6).collect();
+    let characters = take(header.char_count);
+
+    let mut types = Vec::with_capacity(records.len());
+    for record in records {
+        let offset = i32::from_be_bytes(record[..4].try_into().unwrap()) as i64;
+        let start = record[5] as usize;
+        let name = characters.get(start..)?;
+        let end = name
+            .iter()
+            .position(|&byte| byte == 0)
+            .unwrap_or(name.len());
+
+        types.push(LocalType {
+            offset,
+            is_dst: record[4] != 0,
+            abbreviation: String::from_utf8_lossy(&name[..end]).into_owned(),
+        });
+    }
+
+    if type_indices.iter().any(|&index| index >= types.len()) {
+        return None;
+    }
+
+    Some(TimeZone {
+        name: String::new(),
+        transitions,
+        type_indices,
+        types,
+        rule: None,
+    })
+}
+
+/// Returns the number of days since 1970-01-01 for the given date.
+pub fn days_from_civil(date: Date) -> i64 {
+    let year = if date.month <= 2 {
+        date.year - 1
+    } else {
+        date.year
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let month = date.month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + date.day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146097 + day_of_era - 719468
+}
+
+/// Returns the date that lies the given number of days after 1970-01-01.
+pub fn civil_from_days(days: i64) -> Date {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    Date { year, month, day }
+}
+
+/// Returns the number of days in the given month.
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-03-10 07:00 UTC, when New York moved to daylight saving time.
+    const DST_START_2024: i64 = 1_710_054_000;
+    /// 2024-11-03 06:00 UTC, when New York moved back to standard time.
+    const DST_END_2024: i64 = 1_730_613_600;
+
+    fn date(year: i64, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    /// Builds a TZif file with the given transitions (time and type index) and local time
+    /// types (offset, DST flag and abbreviation). Files past version 1 get a 64-bit block and
+    /// `footer` as their POSIX TZ string.
+    fn tzif(
+        version: u8,
+        transitions: &[(i64, u8)],
+        types: &[(i32, bool, &str)],
+        footer: &str,
+    ) -> Vec<u8> {
+        let mut records = Vec::new();
+        let mut characters = Vec::new();
+        for &(offset, is_dst, abbreviation) in types {
+            records.extend(offset.to_be_bytes());
+            records.push(is_dst as u8);
+            records.push(characters.len() as u8);
+            characters.extend(abbreviation.bytes());
+            characters.push(0);
+        }
+
+        let block = |time_size: usize| {
+            let mut data = b"TZif".to_vec();
+            data.push(version);
+            data.extend([0; 15]);
+            for count in [0, 0, 0, transitions.len(), types.len(), characters.len()] {
+                data.extend((count as u32).to_be_bytes());
+            }
+            for &(time, _) in transitions {
+                match time_size {
+                    4 => data.extend((time as i32).to_be_bytes()),
+                    _ => data.extend(time.to_be_bytes()),
+                }
+            }
+            data.extend(transitions.iter().map(|&(_, index)| index));
+            data.extend(&records);
+            data.extend(&characters);
+            data
+        };
+
+        let mut data = block(4);
+        if version != 0 {
+            data.extend(block(8));
+            data.extend(format!("\n{footer}\n").bytes());
+        }
+        data
+    }
+
+    fn new_york() -> Vec<u8> {
+        tzif(
+            b'2',
+            &[(-2_717_650_800, 1), (DST_START_2024, 2)],
+            &[
+                (-17_762, false, "LMT"),
+                (-18_000, false, "EST"),
+                (-14_400, true, "EDT"),
+            ],
+            "EST5EDT,M3.2.0,M11.1.0",
+        )
+    }
+
+    /// A zone that follows a POSIX TZ rule at all times.
+    fn rule_zone(rule: &str) -> TimeZone {
+        let rule = Rule::parse(rule).unwrap();
+        TimeZone {
+            name: String::new(),
+            transitions: Vec::new(),
+            type_indices: Vec::new(),
+            types: vec![rule.standard.clone()],
+            rule: Some(rule),
+        }
+    }
+
+    #[test]
+    fn days_from_civil_counts_from_the_epoch() {
+        assert_eq!(days_from_civil(date(1970, 1, 1)), 0);
+        assert_eq!(days_from_civil(date(1969, 12, 31)), -1);
+        assert_eq!(days_from_civil(date(2000, 3, 1)), 11_017);
+        assert_eq!(days_from_civil(date(2024, 2, 29)), 19_782);
+        assert_eq!(days_from_civil(date(1600, 1, 1)), -135_140);
+    }
+
+    #[test]
+    fn civil_from_days_inverts_days_from_civil() {
+        for days in (-800_000..800_000).step_by(997).chain(19_770..19_800) {
+            assert_eq!(days_from_civil(civil_from_days(days)), days);
+        }
+
+        assert_eq!(civil_from_days(-1), date(1969, 12, 31));
+        assert_eq!(civil_from_days(19_782), date(2024, 2, 29));
+        assert_eq!(civil_from_days(19_783), date(2024, 3, 1));
+    }
+
+    #[test]
+    fn days_in_month_follows_the_leap_year_rules() {
+        assert_eq!(days_in_month(2024, 2), 29);
+        assert_eq!(days_in_month(2023, 2), 28);
+        assert_eq!(days_in_month(1900, 2), 28);
+        assert_eq!(days_in_month(2000, 2), 29);
+        assert_eq!(days_in_month(2024, 4), 30);
+        assert_eq!(days_in_month(2024, 12), 31);
+    }
+
+    #[test]
+    fn rule_date_weekday_finds_the_nth_and_last_weekday() {
+        // The second Sunday of March and the first Sunday of November 2024.
+        assert_eq!(
+            RuleDate::Weekday(3, 2, 0).day(2024),
+            days_from_civil(date(2024, 3, 10))
+        );
+        assert_eq!(
+            RuleDate::Weekday(11, 1, 0).day(2024),
+            days_from_civil(date(2024, 11, 3))
+        );
+
+        // Week 5 is the last one, whether or not the month has five of that weekday.
+        assert_eq!(
+            RuleDate::Weekday(3, 5, 0).day(2024),
+            days_from_civil(date(2024, 3, 31))
+        );
+        assert_eq!(
+            RuleDate::Weekday(10, 5, 0).day(2024),
+            days_from_civil(date(2024, 10, 27))
+        );
+        assert_eq!(
+            RuleDate::Weekday(2, 5, 4).day(2024),
+            days_from_civil(date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn rule_date_julian_skips_february_29_and_ordinal_does_not() {
+        assert_eq!(
+            RuleDate::Julian(59).day(2024),
+            days_from_civil(date(2024, 2, 28))
+        );
+        assert_eq!(
+            RuleDate::Julian(60).day(2024),
+            days_from_civil(date(2024, 3, 1))
+        );
+        assert_eq!(
+            RuleDate::Julian(60).day(2023),
+            days_from_civil(date(2023, 3, 1))
+        );
+        assert_eq!(
+            RuleDate::Ordinal(0).day(2024),
+            days_from_civil(date(2024, 1, 1))
+        );
+        assert_eq!(
+            RuleDate::Ordinal(59).day(2024),
+            days_from_civil(date(2024, 2, 29))
+        );
+    }
+
+    #[test]
+    fn rule_parses_posix_tz_strings() {
+        let rule = Rule::parse("EST5EDT,M3.2.0,M11.1.0").unwrap();
+        assert_eq!(rule.standard.offset, -18_000);
+        assert_eq!(rule.standard.abbreviation, "EST");
+        let (daylight, _, start_time, _, end_time) = rule.daylight.unwrap();
+        assert_eq!(daylight.offset, -14_400);
+        assert_eq!(daylight.abbreviation, "EDT");
+        assert_eq!((start_time, end_time), (7_200, 7_200));
+
+        let rule = Rule::parse("<+0330>-3:30").unwrap();
+        assert_eq!(rule.standard.offset, 12_600);
+        assert_eq!(rule.standard.abbreviation, "+0330");
+        assert!(rule.daylight.is_none());
+
+        let rule = Rule::parse("CET-1CEST,M3.5.0,M10.5.0/3").unwrap();
+        let (daylight, _, _, _, end_time) = rule.daylight.unwrap();
+        assert_eq!(daylight.offset, 7_200);
+        assert_eq!(end_time, 10_800);
+
+        // Without dates, the US rule applies.
+        assert!(Rule::parse("EST5EDT").unwrap().daylight.is_some());
+    }
+
+    #[test]
+    fn rule_rejects_malformed_posix_tz_strings() {
+        for text in [
+            "",
+            "ES5",
+            "EST",
+            "EST5EDT,M3.2.0",
+            "EST5EDT,M13.2.0,M11.1.0",
+            "EST5EDT,M3.6.0,M11.1.0",
+            "EST5EDT,M3.2.7,M11.1.0",
+            "EST5EDT,J0,J365",
+            "EST5EDT,M3.2.0,M11.1.0,",
+        ] {
+            assert!(Rule::parse(text).is_none(), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn rule_switches_at_the_transitions() {
+        let zone = rule_zone("EST5EDT,M3.2.0,M11.1.0");
+
+        assert_eq!(zone.local_type(DST_START_2024 - 1).abbreviation, "EST");
+        assert_eq!(zone.local_type(DST_START_2024).abbreviation, "EDT");
+        assert_eq!(zone.local_type(DST_END_2024 - 1).abbreviation, "EDT");
+        assert_eq!(zone.local_type(DST_END_2024).abbreviation, "EST");
+    }
+
+    #[test]
+    fn rule_handles_daylight_time_across_the_new_year() {
+        let zone = rule_zone("AEST-10AEDT,M10.1.0,M4.1.0/3");
+
+        // 2024-01-15 and 2024-07-15 at noon UTC.
+        assert_eq!(zone.local_type(1_705_320_000).offset, 39_600);
+        assert_eq!(zone.local_type(1_721_044_800).offset, 36_000);
+    }
+
+    #[test]
+    fn to_unix_seconds_resolves_ordinary_times() {
+        let zone = rule_zone("EST5EDT,M3.2.0,M11.1.0");
+        let wall = days_from_civil(date(2024, 7, 1)) * SECONDS_PER_DAY + 12 * 60 * 60;
+
+        assert_eq!(zone.to_unix_seconds(wall), wall + 14_400);
+    }
+
+    #[test]
+    fn to_unix_seconds_moves_times_in_a_gap_forward() {
+        let zone = rule_zone("EST5EDT,M3.2.0,M11.1.0");
+
+        // 02:30 does not exist on 2024-03-10; it becomes 03:30 EDT.
+        let wall = days_from_civil(date(2024, 3, 10)) * SECONDS_PER_DAY + 2 * 60 * 60 + 30 * 60;
+        assert_eq!(zone.to_unix_seconds(wall), DST_START_2024 + 30 * 60);
+    }
+
+    #[test]
+    fn to_unix_seconds_picks_the_earlier_of_repeated_times() {
+        let zone = rule_zone("EST5EDT,M3.2.0,M11.1.0");
+
+        // 01:30 happens twice on 2024-11-03; the first is 01:30 EDT.
+        let wall = days_from_civil(date(2024, 11, 3)) * SECONDS_PER_DAY + 60 * 60 + 30 * 60;
+        assert_eq!(zone.to_unix_seconds(wall), DST_END_2024 - 30 * 60);
+    }
+
+    #[test]
+    fn parse_tzif_reads_transitions_and_the_footer() {
+        let zone = parse_tzif(&new_york()).unwrap();
+
+        assert_eq!(zone.local_type(-3_000_000_000).abbreviation, "LMT");
+        assert_eq!(zone.local_type(0).abbreviation, "EST");
+        assert_eq!(zone.local_type(DST_START_2024 - 1).abbreviation, "EST");
+        assert_eq!(zone.local_type(DST_START_2024).offset, -14_400);
+        assert!(zone.local_type(DST_START_2024).is_dst);
+
+        // Past the last transition, the footer rule takes over.
+        assert_eq!(zone.local_type(DST_END_2024).abbreviation, "EST");
+    }
+
+    #[test]
+    fn parse_tzif_reads_version_1_files() {
+        let data = tzif(
+            0,
+            &[(0, 1)],
+            &[(3_600, false, "CET"), (7_200, true, "CEST")],
+            "",
+        );
+        let zone = parse_tzif(&data).unwrap();
+
+        assert_eq!(zone.local_type(-1).offset, 3_600);
+        assert_eq!(zone.local_type(0).offset, 7_200);
+        assert_eq!(zone.local_type(i64::MAX / 2).offset, 7_200);
+    }
+
+    #[test]
+    fn parse_tzif_rejects_malformed_files() {
+        let data = new_york();
+        assert!(parse_tzif(&data[..data.len() / 2]).is_none());
+        assert!(parse_tzif(&data[..10]).is_none());
+        assert!(parse_tzif(b"").is_none());
+
+        for version in [1, b'1', b'5', b'/', 0xff] {
+            let mut data = data.clone();
+            data[4] = version;
+            assert!(parse_tzif(&data).is_none(), "version {version}");
+        }
+
+        let mut data = data.clone();
+        data[..4].copy_from_slice(b"TZig");
+        assert!(parse_tzif(&data).is_none());
+
+        let data = tzif(b'2', &[(0, 5)], &[(0, false, "UTC")], "UTC0");
+        assert!(parse_tzif(&data).is_none());
+
+        let data = tzif(b'2', &[], &[], "UTC0");
+        assert!(parse_tzif(&data).is_none());
+    }
+}
@@ -0,0 +1,548 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::parse::{ErrorKind, ParsingError, MAX_DURATION};
+use crate::tz::{civil_from_days, days_from_civil, days_in_month, Date, TimeZone, SECONDS_PER_DAY};
+
+/// A time of day, down to nanoseconds.
+#[derive(Debug, Clone, Copy)]
+struct Time {
+    hour: u32,
+    minute: u32,
+    second: u32,
+    nanosecond: u32,
+}
+
+/// An absolute point in time to count down to, and the zone it was given in, if any.
+pub struct Target {
+    pub at: SystemTime,
+    pub zone: Option<TimeZone>,
+}
+
+/// The clock a wall-clock date and time is read in.
+enum Clock<'a> {
+    Local,
+    Offset(i64),
+    Zone(&'a TimeZone),
+}
+
+/// Parses an absolute target time.
+/// Accepted formats are HH:MM[:SS], YYYY-MM-DD HH:MM[:SS] and RFC 3339 timestamps
+/// (e.g., 2024-05-01T14:30:00+02:00), optionally followed by an IANA time zone name
+/// (e.g., 09:00 America/New_York). A time without a date that has already passed today
+/// refers to tomorrow.
+pub fn parse_until(text: &str) -> Result<Target, ParsingError> {
+    let text = text.trim();
+
+    let (text, zone) = match text.rsplit_once(char::is_whitespace) {
+        Some((rest, name)) if name.starts_with(char::is_alphabetic) => {
+            let zone = TimeZone::load(name).ok_or(ErrorKind::UnknownTimeZone)?;
+            (rest.trim_end(), Some(zone))
+        }
+        _ => (text, None),
+    };
+
+    let at = parse_target(text, zone.as_ref(), SystemTime::now())?;
+
+    Ok(Target { at, zone })
+}
+
+impl Target {
+    /// Returns how long it is from now until the target.
+    pub fn remaining(&self) -> Result<Duration, ParsingError> {
+        self.remaining_since(SystemTime::now())
+    }
+
+    /// Returns how long it is from `now` until the target.
+    fn remaining_since(&self, now: SystemTime) -> Result<Duration, ParsingError> {
+        let remaining = self
+            .at
+            .duration_since(now)
+            .map_err(|_| ErrorKind::PastTarget)?;
+
+        if remaining > MAX_DURATION {
+            return Err(ErrorKind::Overflow.into());
+        }
+
+        Ok(remaining)
+    }
+
+    /// Describes the target in local time, followed by the time in its zone if one was given.
+    pub fn describe(&self) -> String {
+        let seconds = unix_seconds(self.at);
+        let local = format_local(seconds);
+
+        match &self.zone {
+            Some(zone) => {
+                let local_type = zone.local_type(seconds);
+                let (date, time) = split_seconds(seconds + local_type.offset);
+                format!(
+                    "{local} ({} {} {}, {})",
+                    format_date(date),
+                    format_time(time),
+                    local_type.abbreviation,
+                    zone.name
+                )
+            }
+            None => local,
+        }
+    }
+}
+
+/// Returns how long it is from `now` until the given local time of day, `days` days after
+/// today. Without a day, a time that has already passed today refers to tomorrow.
+pub fn until_time_of_day(
+    days: Option<i64>,
+    hour: u32,
+    minute: u32,
+    now: SystemTime,
+) -> Result<Duration, ParsingError> {
+    let time = Time {
+        hour,
+        minute,
+        second: 0,
+        nanosecond: 0,
+    };
+
+    let today = days_from_civil(Clock::Local.today(now));
+    let mut at = Clock::Local.resolve(civil_from_days(today + days.unwrap_or(0)), time)?;
+    if days.is_none() && at <= now {
+        at = Clock::Local.resolve(civil_from_days(today + 1), time)?;
+    }
+
+    Target { at, zone: None }.remaining_since(now)
+}
+
+/// Resolves a textual target relative to `now`.
+fn parse_target(
+    text: &str,
+    zone: Option<&TimeZone>,
+    now: SystemTime,
+) -> Result<SystemTime, ParsingError> {
+    let (date, rest) = match text.split_once([' ', 'T', 't']) {
+        Some((date, rest)) => (Some(parse_date(date)?), rest.trim_start()),
+        None => (None, text),
+    };
+
+    let (time, offset) = split_offset(rest)?;
+    let time = parse_time(time)?;
+
+    let clock = match (offset, zone) {
+        (Some(_), Some(_)) => return Err(ErrorKind::MalformedTime.into()),
+        (Some(offset), None) => Clock::Offset(offset),
+        (None, Some(zone)) => Clock::Zone(zone),
+        (None, None) => Clock::Local,
+    };
+
+    if let Some(date) = date {
+        return clock.resolve(date, time);
+    }
+
+    let today = clock.today(now);
+    let target = clock.resolve(today, time)?;
+    if target > now {
+        return Ok(target);
+    }
+
+    let tomorrow = civil_from_days(days_from_civil(today) + 1);
+    clock.resolve(tomorrow, time)
+}
+
+impl Clock<'_> {
+    /// Converts a wall-clock date and time on this clock into a point in time.
+    fn resolve(&self, date: Date, time: Time) -> Result<SystemTime, ParsingError> {
+        let wall = days_from_civil(date) * SECONDS_PER_DAY
+            + time.hour as i64 * 60 * 60
+            + time.minute as i64 * 60
+            + time.second as i64;
+
+        let seconds = match self {
+            Clock::Local => return local_time(date, time),
+            Clock::Offset(offset) => wall - offset,
+            Clock::Zone(zone) => zone.to_unix_seconds(wall),
+        };
+
+        Ok(from_unix_seconds(seconds, time.nanosecond))
+    }
+
+    /// Returns the calendar date on this clock at the given point in time.
+    fn today(&self, now: SystemTime) -> Date {
+        let seconds = unix_seconds(now);
+
+        let offset = match self {
+            Clock::Local => return local_date(now),
+            Clock::Offset(offset) => *offset,
+            Clock::Zone(zone) => zone.local_type(seconds).offset,
+        };
+
+        split_seconds(seconds + offset).0
+    }
+}
+
+/// Parses a YYYY-MM-DD date.
+fn parse_date(text: &str) -> Result<Date, ParsingError> {
+    let fields: Vec<&str> = text.split('-').collect();
+    let [year, month, day] = fields[..] else {
+        return Err(ErrorKind::MalformedDate.into());
+    };
+
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(ErrorKind::MalformedDate.into());
+    }
+
+    let (Some(year), Some(month), Some(day)) =
+        (parse_digits(year), parse_digits(month), parse_digits(day))
+    else {
+        return Err(ErrorKind::MalformedDate.into());
+    };
+
+    let year = year as i64;
+    if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+        return Err(ErrorKind::MalformedDate.into());
+    }
+
+    Ok(Date { year, month, day })
+}
+
+/// Parses a HH:MM[:SS[.FFF]] time of day.
+fn parse_time(text: &str) -> Result<Time, ParsingError> {
+    let fields: Vec<&str> = text.split(':').collect();
+    let (hour, minute, second) = match fields[..] {
+        [hour, minute] => (hour, minute, "00"),
+        [hour, minute, second] => (hour, minute, second),
+        _ => return Err(ErrorKind::MalformedTime.into()),
+    };
+
+    let (second, fraction) = second.split_once('.').unwrap_or((second, ""));
+
+    if !(1..=2).contains(&hour.len()) || minute.len() != 2 || second.len() != 2 {
+        return Err(ErrorKind::MalformedTime.into());
+    }
+
+    let (Some(hour), Some(minute), Some(second)) = (
+        parse_digits(hour),
+        parse_digits(minute),
+        parse_digits(second),
+    ) else {
+        return Err(ErrorKind::MalformedTime.into());
+    };
+
+    // RFC 3339 allows a leap second, which is folded into the preceding one.
+    if hour > 23 || minute > 59 || second > 60 {
+        return Err(ErrorKind::MalformedTime.into());
+    }
+
+    let nanosecond = if fraction.is_empty() {
+        0
+    } else {
+        let digits = &fraction[..fraction.len().min(9)];
+        let value = parse_digits(digits).ok_or(ErrorKind::MalformedTime)?;
+        value * 10u32.pow(9 - digits.len() as u32)
+    };
+
+    Ok(Time {
+        hour,
+        minute,
+        second: second.min(59),
+        nanosecond,
+    })
+}
+
+/// Splits an RFC 3339 offset (`Z`, `+HH:MM` or `-HH:MM`) off a time and returns it in seconds.
+fn split_offset(text: &str) -> Result<(&str, Option<i64>), ParsingError> {
+    if let Some(time) = text.strip_suffix(['Z', 'z']) {
+        return Ok((time, Some(0)));
+    }
+
+    let Some(index) = text.find(['+', '-']) else {
+        return Ok((text, None));
+    };
+
+    let (time, offset) = text.split_at(index);
+    let sign = if offset.starts_with('-') { -1 } else { 1 };
+
+    let Some((hours, minutes)) = offset[1..].split_once(':') else {
+        return Err(ErrorKind::MalformedTime.into());
+    };
+
+    if hours.len() != 2 || minutes.len() != 2 {
+        return Err(ErrorKind::MalformedTime.into());
+    }
+
+    match (parse_digits(hours), parse_digits(minutes)) {
+        (Some(hours), Some(minutes)) if hours < 24 && minutes < 60 => {
+            Ok((time, Some(sign * (hours as i64 * 60 + minutes as i64) * 60)))
+        }
+        _ => Err(ErrorKind::MalformedTime.into()),
+    }
+}
+
+/// Parses a non-empty string of ASCII digits.
+fn parse_digits(text: &str) -> Option<u32> {
+    if text.is_empty() || !text.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    text.parse().ok()
+}
+
+/// Converts a local wall-clock date and time into a point in time, using the system time zone.
+fn local_time(date: Date, time: Time) -> Result<SystemTime, ParsingError> {
+    // SAFETY: `tm` is plain old data, and mktime only reads and normalizes the fields we set.
+    let seconds = unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        tm.tm_year = (date.year - 1900) as libc::c_int;
+        tm.tm_mon = date.month as libc::c_int - 1;
+        tm.tm_mday = date.day as libc::c_int;
+        tm.tm_hour = time.hour as libc::c_int;
+        tm.tm_min = time.minute as libc::c_int;
+        tm.tm_sec = time.second as libc::c_int;
+        tm.tm_isdst = -1;
+
+        libc::mktime(&mut tm)
+    };
+
+    if seconds == -1 {
+        return Err(ErrorKind::MalformedDate.into());
+    }
+
+    Ok(from_unix_seconds(seconds as i64, time.nanosecond))
+}
+
+/// Breaks a Unix time down into local calendar fields.
+fn localtime(seconds: i64) -> libc::tm {
+    let seconds = seconds as libc::time_t;
+
+    // SAFETY: localtime_r only writes into the `tm` we hand it.
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        libc::localtime_r(&seconds, &mut tm);
+        tm
+    }
+}
+
+/// Returns the calendar date of local calendar fields.
+fn tm_date(tm: &libc::tm) -> Date {
+    Date {
+        year: tm.tm_year as i64 + 1900,
+        month: tm.tm_mon as u32 + 1,
+        day: tm.tm_mday as u32,
+    }
+}
+
+/// Returns the seconds into the day of local calendar fields.
+fn tm_time(tm: &libc::tm) -> i64 {
+    (tm.tm_hour as i64 * 60 + tm.tm_min as i64) * 60 + tm.tm_sec as i64
+}
+
+/// Returns the local calendar date at the given point in time.
+fn local_date(now: SystemTime) -> Date {
+    tm_date(&localtime(unix_seconds(now)))
+}
+
+/// Formats a point in time as a local HH:MM:SS, preceded by its date unless that is today.
+pub fn format_wall_clock(at: SystemTime) -> String {
+    let tm = localtime(unix_seconds(at));
+    let time = format_time(tm_time(&tm));
+
+    let date = tm_date(&tm);
+    if date == local_date(SystemTime::now()) {
+        time
+    } else {
+        format!("{} {time}", format_date(date))
+    }
+}
+
+/// Returns the whole seconds since the Unix epoch.
+fn unix_seconds(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(error) => -(error.duration().as_secs_f64().ceil() as i64),
+    }
+}
+
+/// Builds a point in time from seconds since the Unix epoch and a subsecond part.
+fn from_unix_seconds(seconds: i64, nanosecond: u32) -> SystemTime {
+    let whole = Duration::from_secs(seconds.unsigned_abs());
+    let epoch = if seconds >= 0 {
+        UNIX_EPOCH + whole
+    } else {
+        UNIX_EPOCH - whole
+    };
+
+    epoch + Duration::from_nanos(nanosecond as u64)
+}
+
+/// Splits wall-clock seconds since the epoch into a date and the seconds into that day.
+fn split_seconds(seconds: i64) -> (Date, i64) {
+    (
+        civil_from_days(seconds.div_euclid(SECONDS_PER_DAY)),
+        seconds.rem_euclid(SECONDS_PER_DAY),
+    )
+}
+
+/// Formats a date as YYYY-MM-DD.
+fn format_date(date: Date) -> String {
+    format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+}
+
+/// Formats seconds into a day as HH:MM:SS.
+fn format_time(seconds: i64) -> String {
+    format!(
+        "{:02}:{:02}:{:02}",
+        seconds / (60 * 60),
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// Formats a Unix time as a local YYYY-MM-DD HH:MM:SS followed by the zone abbreviation.
+fn format_local(seconds: i64) -> String {
+    let tm = localtime(seconds);
+
+    // SAFETY: `tm_zone` points into static storage owned by the C library.
+    let abbreviation = if tm.tm_zone.is_null() {
+        String::new()
+    } else {
+        unsafe { std::ffi::CStr::from_ptr(tm.tm_zone) }
+            .to_string_lossy()
+            .into_owned()
+    };
+
+    format!(
+        "{} {} {abbreviation}",
+        format_date(tm_date(&tm)),
+        format_time(tm_time(&tm))
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 2024-05-01 10:00:00 UTC.
+    const NOW: i64 = 1_714_557_600;
+
+    fn target(text: &str) -> Result<i64, ErrorKind> {
+        parse_target(text, None, from_unix_seconds(NOW, 0))
+            .map(unix_seconds)
+            .map_err(|error| error.kind)
+    }
+
+    #[test]
+    fn parse_date_checks_the_calendar() {
+        assert_eq!(
+            parse_date("2024-02-29").unwrap(),
+            Date {
+                year: 2024,
+                month: 2,
+                day: 29
+            }
+        );
+
+        for text in [
+            "2023-02-29",
+            "2024-13-01",
+            "2024-04-31",
+            "2024-00-10",
+            "24-01-01",
+            "2024-1-01",
+            "2024/01/01",
+        ] {
+            assert_eq!(
+                parse_date(text).unwrap_err().kind,
+                ErrorKind::MalformedDate,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_time_reads_hours_minutes_seconds_and_fractions() {
+        let time = parse_time("9:05").unwrap();
+        assert_eq!(
+            (time.hour, time.minute, time.second, time.nanosecond),
+            (9, 5, 0, 0)
+        );
+
+        let time = parse_time("12:00:00.25").unwrap();
+        assert_eq!(time.nanosecond, 250_000_000);
+
+        let time = parse_time("12:00:00.1234567891").unwrap();
+        assert_eq!(time.nanosecond, 123_456_789);
+
+        // A leap second is folded into the second before it.
+        assert_eq!(parse_time("23:59:60").unwrap().second, 59);
+
+        for text in [
+            "24:00",
+            "12:60",
+            "12:5",
+            "123:00",
+            "12",
+            "12:00:00:00",
+            "12:00:0x",
+        ] {
+            assert_eq!(
+                parse_time(text).unwrap_err().kind,
+                ErrorKind::MalformedTime,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn split_offset_reads_rfc_3339_offsets() {
+        assert_eq!(split_offset("14:30").unwrap(), ("14:30", None));
+        assert_eq!(split_offset("14:30:00Z").unwrap(), ("14:30:00", Some(0)));
+        assert_eq!(split_offset("14:30+02:00").unwrap(), ("14:30", Some(7_200)));
+        assert_eq!(
+            split_offset("14:30-05:30").unwrap(),
+            ("14:30", Some(-19_800))
+        );
+
+        for text in [
+            "14:30+2",
+            "14:30+02",
+            "14:30+24:00",
+            "14:30+02:60",
+            "14:30+0a:00",
+        ] {
+            assert_eq!(
+                split_offset(text).unwrap_err().kind,
+                ErrorKind::MalformedTime,
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_target_resolves_dates_and_offsets() {
+        assert_eq!(target("2024-05-01T14:30:00+02:00"), Ok(1_714_566_600));
+        assert_eq!(target("2024-05-01 12:30Z"), Ok(1_714_566_600));
+        assert_eq!(target("2024-05-01t12:30:00z"), Ok(1_714_566_600));
+        assert_eq!(target("2024-02-30 12:00Z"), Err(ErrorKind::MalformedDate));
+    }
+
+    #[test]
+    fn parse_target_rolls_passed_times_over_to_tomorrow() {
+        assert_eq!(target("11:00Z"), Ok(NOW + 60 * 60));
+        assert_eq!(target("09:00Z"), Ok(NOW + 23 * 60 * 60));
+        assert_eq!(target("10:00Z"), Ok(NOW + 24 * 60 * 60));
+        assert_eq!(target("12:00+02:00"), Ok(NOW + 24 * 60 * 60));
+    }
+
+    #[test]
+    fn split_seconds_handles_times_before_the_epoch() {
+        let (date, seconds) = split_seconds(-1);
+        assert_eq!(
+            date,
+            Date {
+                year: 1969,
+                month: 12,
+                day: 31
+            }
+        );
+        assert_eq!(seconds, SECONDS_PER_DAY - 1);
+        assert_eq!(format_time(seconds), "23:59:59");
+    }
+}